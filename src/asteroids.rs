@@ -0,0 +1,168 @@
+use std::f32::consts::TAU;
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{mat4_to_mat3, Mat4, Vec3};
+use crate::generator::Rng;
+use crate::material::Material;
+use crate::mesh::{Mesh, NormalMode};
+use crate::noise::{self, Fractal};
+use crate::sphere;
+
+// 320 triángulos alcanzan para una roca que rara vez ocupa más de unas decenas de píxeles
+const ROCK_SUBDIVISIONS: u32 = 2;
+const ROCK_RADIUS: f32 = 0.5;
+// Desviación máxima del radio de la roca respecto a la esfera
+const ROCK_ROUGHNESS: f32 = 0.35;
+const ROCK_SHAPE: Fractal = Fractal::fbm(4);
+// Grosor del cinturón respecto a su ancho
+const BELT_THICKNESS: f32 = 0.075;
+const MAX_SPIN_SPEED: f32 = 0.03;
+
+pub struct Asteroid {
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub current_angle: f32,
+    // Altura sobre el plano orbital
+    pub height: f32,
+    // Escala por eje: la misma malla da rocas alargadas o aplastadas
+    pub scale: Vec3,
+    pub spin_axis: Vec3,
+    pub spin_speed: f32,
+    pub spin_angle: f32,
+    pub position: Vec3,
+    // Albedo relativo, para que los puntos lejanos no tengan todos el mismo brillo
+    pub brightness: f32,
+}
+
+impl Asteroid {
+    pub fn update_position(&mut self, delta_time: f32) {
+        self.current_angle += self.orbit_speed * delta_time;
+        self.spin_angle += self.spin_speed * delta_time;
+        self.position = Vec3::new(
+            self.orbit_radius * self.current_angle.cos(),
+            self.height,
+            self.orbit_radius * self.current_angle.sin(),
+        );
+    }
+
+    // Como Planet::get_model_matrix, con el giro sobre su eje y la escala por eje
+    pub fn get_model_matrix(&self) -> Mat4 {
+        Mat4::new_translation(&self.position)
+            * nalgebra_glm::rotation(self.spin_angle, &self.spin_axis)
+            * Mat4::new_nonuniform_scaling(&self.scale)
+    }
+
+    // Punto de la esfera base (en el espacio del modelo) cuya normal en el mundo apunta hacia `direction`.
+    // La normal del mundo es la del modelo por la inversa traspuesta, así que se deshace con la traspuesta
+    pub fn facing_point(&self, direction: &Vec3) -> Vec3 {
+        let model = mat4_to_mat3(&self.get_model_matrix());
+        (model.transpose() * direction).try_normalize(f32::EPSILON).unwrap_or(Vec3::y()) * ROCK_RADIUS
+    }
+
+    // Radio de la esfera que contiene la roca, en unidades del mundo
+    pub fn bounding_radius(&self) -> f32 {
+        self.scale.max() * ROCK_RADIUS * (1.0 + ROCK_ROUGHNESS)
+    }
+}
+
+// Miles de rocas que comparten una malla y un material; solo cambia la matriz de modelo de cada una
+pub struct AsteroidBelt {
+    pub name: String,
+    pub orbit_radius: f32,
+    // Velocidad a la distancia media; el resto sigue la tercera ley de Kepler
+    pub orbit_speed: f32,
+    pub width: f32,
+    // Tamaño de las rocas más grandes
+    pub size: f32,
+    pub seed: u64,
+    pub material: Material,
+    pub mesh: Mesh,
+    pub asteroids: Vec<Asteroid>,
+}
+
+impl AsteroidBelt {
+    pub fn new(name: &str, orbit_radius: f32, orbit_speed: f32, size: f32, material: Material) -> Self {
+        AsteroidBelt {
+            name: name.to_string(),
+            orbit_radius,
+            orbit_speed,
+            width: 0.0,
+            size,
+            seed: 0,
+            material,
+            mesh: Mesh::new(Vec::new(), Vec::new()),
+            asteroids: Vec::new(),
+        }
+    }
+
+    // Genera las rocas y su malla; la misma semilla da siempre el mismo cinturón
+    pub fn with_asteroids(mut self, count: usize, width: f32, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        self.asteroids = (0..count)
+            .map(|_| {
+                // La suma de dos uniformes (en -1..1) concentra las rocas en el centro del cinturón,
+                // que ocupa una franja de `width` unidades
+                let offset = rng.range(-0.5, 0.5) + rng.range(-0.5, 0.5);
+                let radius = self.orbit_radius + offset * width * 0.5;
+                let scale = Vec3::new(rng.range(0.6, 1.0), rng.range(0.5, 0.9), 1.0) * self.size * rng.range(0.3, 1.0);
+                let spin_axis = Vec3::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0), rng.range(-1.0, 1.0))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(Vec3::y());
+                let mut asteroid = Asteroid {
+                    orbit_radius: radius,
+                    orbit_speed: self.orbit_speed * (self.orbit_radius / radius).powf(1.5),
+                    current_angle: rng.range(0.0, TAU),
+                    height: rng.range(-1.0, 1.0) * width * BELT_THICKNESS,
+                    scale,
+                    spin_axis,
+                    spin_speed: rng.range(-MAX_SPIN_SPEED, MAX_SPIN_SPEED),
+                    spin_angle: rng.range(0.0, TAU),
+                    position: Vec3::zeros(),
+                    brightness: rng.range(0.6, 1.0),
+                };
+                asteroid.update_position(0.0);
+                asteroid
+            })
+            .collect();
+        self.width = width;
+        self.seed = seed;
+        self.mesh = rock_mesh(seed);
+        self
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for asteroid in self.asteroids.iter_mut() {
+            asteroid.update_position(delta_time);
+        }
+    }
+}
+
+// Icosfera deformada con ruido fractal; las normales se recalculan sobre la forma final
+fn rock_mesh(seed: u64) -> Mesh {
+    let noise = FastNoiseLite::with_seed(seed as i32);
+    let mut mesh = sphere::icosphere(ROCK_SUBDIVISIONS, ROCK_RADIUS);
+    for vertex in mesh.vertices.iter_mut() {
+        let direction = noise::sphere_point(&vertex.position);
+        let displacement = ROCK_SHAPE.sample_3d(&noise, &(direction * 1.5));
+        vertex.position = direction * ROCK_RADIUS * (1.0 + ROCK_ROUGHNESS * displacement);
+    }
+    mesh.compute_normals(NormalMode::Smooth);
+    mesh.compute_tangents();
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Incluso con belt_width casi el doble de orbit_radius ninguna roca llega al centro
+    #[test]
+    fn rocks_stay_inside_the_belt() {
+        let (orbit_radius, width) = (10.0, 19.9);
+        let belt = AsteroidBelt::new("Cinturón", orbit_radius, 0.001, 0.1, Material::new("mars").unwrap())
+            .with_asteroids(2000, width, 7);
+        for asteroid in &belt.asteroids {
+            assert!((asteroid.orbit_radius - orbit_radius).abs() <= width * 0.5);
+            assert!(asteroid.orbit_radius > 0.0 && asteroid.orbit_speed.is_finite());
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use nalgebra_glm::{cross, dot, mat4_to_mat3, Vec3, Vec4};
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::Uniforms;
+
+// Base tangente-bitangente de la superficie; la bitangente usa el signo guardado en tangent.w
+fn tangent_frame(normal: &Vec3, tangent: &Vec4) -> (Vec3, Vec3) {
+    let direction = (tangent.xyz() - normal * dot(normal, &tangent.xyz()))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+    let bitangent = cross(normal, &direction) * tangent.w.signum();
+    (direction, bitangent)
+}
+
+// Perturba la normal con la pendiente de un campo de altura evaluado en el espacio del modelo.
+// `strength` escala la pendiente y `step` es la distancia de las diferencias finitas.
+// La normal del fragmento llega al mundo con la matriz normal y la tangente con la del modelo: la
+// traspuesta del modelo y su inversa las devuelven al espacio del modelo, donde se calcula el relieve.
+// Así sirve también para instancias giradas o con escala no uniforme, como los asteroides
+pub fn bump_normal(
+    fragment: &Fragment,
+    uniforms: &Uniforms,
+    strength: f32,
+    step: f32,
+    height: impl Fn(&Vec3) -> f32,
+) -> Vec3 {
+    let model = mat4_to_mat3(&uniforms.model_matrix);
+    let Some(inverse) = model.try_inverse() else {
+        return fragment.normal;
+    };
+    let normal = (model.transpose() * fragment.normal).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+    let tangent = inverse * fragment.tangent.xyz();
+    let tangent = Vec4::new(tangent.x, tangent.y, tangent.z, fragment.tangent.w);
+
+    let perturbed = perturb_normal(&normal, &tangent, &fragment.vertex_position, strength, step, height);
+    (inverse.transpose() * perturbed).try_normalize(f32::EPSILON).unwrap_or(fragment.normal)
+}
+
+// Misma perturbación a partir de los datos de un vértice; también sirve para la superficie desplazada
+// p + n * strength * altura(p), cuya normal se inclina con esa misma pendiente
+pub fn perturb_normal(
+    normal: &Vec3,
+    tangent: &Vec4,
+    position: &Vec3,
+    strength: f32,
+    step: f32,
+    height: impl Fn(&Vec3) -> f32,
+) -> Vec3 {
+    let (tangent, bitangent) = tangent_frame(normal, tangent);
+    let base = height(position);
+    let slope_t = (height(&(position + tangent * step)) - base) / step;
+    let slope_b = (height(&(position + bitangent * step)) - base) / step;
+
+    (normal - (tangent * slope_t + bitangent * slope_b) * strength)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(*normal)
+}
+
+// Normal codificada en un mapa de normales en espacio tangente (canales en 0..255 → -1..1)
+pub fn mapped_normal(fragment: &Fragment, sample: Color, strength: f32) -> Vec3 {
+    let (tangent, bitangent) = tangent_frame(&fragment.normal, &fragment.tangent);
+    let [x, y, z] = sample.to_rgb_f32().map(|channel| channel * 2.0 - 1.0);
+
+    (tangent * (x * strength) + bitangent * (y * strength) + fragment.normal * z)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(fragment.normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnoise_lite::FastNoiseLite;
+    use nalgebra_glm::{Mat3, Mat4};
+    use crate::lighting::LightingModel;
+    use crate::model::DEFAULT_SURFACE;
+
+    // Una roca girada y con escala no uniforme, como las instancias de un cinturón
+    #[test]
+    fn bump_follows_rotated_and_scaled_instances() {
+        let model_matrix = Mat4::new_translation(&Vec3::new(3.0, -1.0, 2.0))
+            * nalgebra_glm::rotation(1.1, &Vec3::new(0.3, 0.8, -0.5).normalize())
+            * Mat4::new_nonuniform_scaling(&Vec3::new(2.0, 0.5, 1.3));
+        let noise = FastNoiseLite::new();
+        let uniforms = Uniforms {
+            model_matrix,
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: Mat4::identity(),
+            time: 0,
+            noise: &noise,
+            surface: &DEFAULT_SURFACE,
+            eye: Vec3::zeros(),
+            light_position: Vec3::zeros(),
+            lighting: LightingModel::CookTorrance,
+        };
+
+        // Pendiente de 1 a lo largo de x en el espacio del modelo
+        let (normal, tangent, position) = (Vec3::new(0.0, 0.0, 1.0), Vec4::new(1.0, 0.0, 0.0, 1.0), Vec3::new(0.2, 0.1, 0.4));
+        let height = |point: &Vec3| point.x;
+        let normal_matrix = mat4_to_mat3(&model_matrix).transpose().try_inverse().unwrap_or(Mat3::identity());
+
+        let world_tangent = mat4_to_mat3(&model_matrix) * tangent.xyz();
+        let mut fragment = Fragment::new(0.0, 0.0, 0.5, (normal_matrix * normal).normalize(), position);
+        fragment.tangent = Vec4::new(world_tangent.x, world_tangent.y, world_tangent.z, tangent.w);
+        // Transformada con el modelo, la tangente sigue en el plano de la superficie
+        assert!(world_tangent.dot(&fragment.normal).abs() < 1e-5);
+
+        let expected = (normal_matrix * perturb_normal(&normal, &tangent, &position, 0.5, 0.01, height)).normalize();
+        let bumped = bump_normal(&fragment, &uniforms, 0.5, 0.01, height);
+        assert!((bumped - expected).norm() < 1e-4, "{:?} != {:?}", bumped, expected);
+        assert!((expected - fragment.normal).norm() > 0.1);
+    }
+}
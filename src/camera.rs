@@ -5,7 +5,6 @@ pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
     pub up: Vec3,
-    pub has_changed: bool,
 }
 
 impl Camera {
@@ -14,7 +13,6 @@ impl Camera {
             eye,
             center,
             up,
-            has_changed: true,
         }
     }
 
@@ -0,0 +1,41 @@
+pub struct SimClock {
+    pub elapsed: f64,
+    pub scale: f32,
+    pub paused: bool,
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        SimClock {
+            elapsed: 0.0,
+            scale: 1.0,
+            paused: false,
+        }
+    }
+
+    // Avanza el reloj un paso y devuelve el delta de simulación aplicado
+    pub fn tick(&mut self, step: f32) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let delta = step * self.scale;
+        self.elapsed += delta as f64;
+        delta
+    }
+
+    pub fn speed_up(&mut self) {
+        self.scale = (self.scale * 2.0).min(1024.0);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.scale = (self.scale / 2.0).max(1.0 / 64.0);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn shader_time(&self) -> u32 {
+        self.elapsed as u32
+    }
+}
@@ -18,7 +18,47 @@ impl Color {
         Color { r: 0, g: 0, b: 0 }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub const fn from_hex(hex: u32) -> Self {
+        Color {
+            r: ((hex >> 16) & 0xFF) as u8,
+            g: ((hex >> 8) & 0xFF) as u8,
+            b: (hex & 0xFF) as u8,
+        }
+    }
+
+    // Canales en 0.0..=1.0, como en los archivos MTL y glTF
+    pub fn from_rgb_f32(rgb: [f32; 3]) -> Self {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(channel(rgb[0]), channel(rgb[1]), channel(rgb[2]))
+    }
+
+    pub fn to_rgb_f32(self) -> [f32; 3] {
+        [self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0]
+    }
+
+    // Acepta "#RRGGBB" o "r, g, b"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if let Some(hex) = value.strip_prefix('#') {
+            return u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() == 6)
+                .map(Color::from_hex)
+                .ok_or(format!("Color inválido: {}", value));
+        }
+
+        let channels: Vec<u8> = value
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Color inválido: {}", value))?;
+        match channels[..] {
+            [r, g, b] => Ok(Color::new(r, g, b)),
+            _ => Err(format!("Color inválido: {}", value)),
+        }
+    }
+
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
@@ -61,6 +101,20 @@ impl Mul<f32> for Color {
     }
 }
 
+// Modulación canal a canal (p. ej. color difuso por textura)
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        let channel = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+        }
+    }
+}
+
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
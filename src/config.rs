@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::PathBuf;
+use minifb::Scale;
+use crate::ephemeris;
+use crate::record::{RecordOutput, RecordSettings};
+use crate::sphere::SphereKind;
+use crate::lighting::LightingModel;
+
+const DEFAULT_SCENE: &str = "assets/scenes/solar_system.scene";
+// Escena con los ocho planetas, la que usa --date si no se indica otra
+const EPHEMERIS_SCENE: &str = "assets/scenes/ephemeris.scene";
+
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    pub render_scale: f32,
+    pub window_scale: Scale,
+    pub resizable: bool,
+    pub msaa: usize,
+    pub fxaa: bool,
+    pub scene: String,
+    // Con semilla se genera un sistema en lugar de cargar la escena
+    pub seed: Option<u64>,
+    // Día juliano (UTC) con el que se colocan los planetas según sus efemérides
+    pub date: Option<f64>,
+    pub sphere: SphereKind,
+    pub lod_levels: u32,
+    pub lighting: LightingModel,
+    pub print_scene: bool,
+    pub profile_csv: Option<String>,
+    pub record: Option<RecordSettings>,
+}
+
+#[derive(Default)]
+struct RecordArgs {
+    frames: Option<u32>,
+    step: Option<f32>,
+    size: Option<(usize, usize)>,
+    output: Option<RecordOutput>,
+}
+
+impl Config {
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut config = Config {
+            width: 800,
+            height: 800,
+            render_scale: 1.0,
+            window_scale: Scale::X1,
+            resizable: false,
+            msaa: 1,
+            fxaa: false,
+            scene: String::new(),
+            seed: None,
+            date: None,
+            sphere: SphereKind::Icosphere,
+            lod_levels: 5,
+            lighting: LightingModel::CookTorrance,
+            print_scene: false,
+            profile_csv: None,
+            record: None,
+        };
+        let mut record = RecordArgs::default();
+
+        while let Some(arg) = args.next() {
+            let key = arg
+                .strip_prefix("--")
+                .ok_or(format!("Argumento desconocido: {}", arg))?;
+            if key == "resizable" || key == "fxaa" || key == "print-scene" {
+                config.apply(key, "true", &mut record)?;
+                continue;
+            }
+            let value = args.next().ok_or(format!("Falta el valor de {}", arg))?;
+            if key == "config" {
+                config.load_file(&value, &mut record)?;
+            } else {
+                config.apply(key, &value, &mut record)?;
+            }
+        }
+
+        if config.date.is_some() && config.seed.is_some() {
+            return Err(String::from("--date no se puede combinar con --seed"));
+        }
+        if config.scene.is_empty() {
+            config.scene = String::from(if config.date.is_some() { EPHEMERIS_SCENE } else { DEFAULT_SCENE });
+        }
+
+        if let Some(frames) = record.frames {
+            let (width, height) = record.size.unwrap_or((config.width, config.height));
+            config.record = Some(RecordSettings {
+                frames,
+                step: record.step.unwrap_or(1.0),
+                width,
+                height,
+                output: record.output.unwrap_or(RecordOutput::Images(PathBuf::from("frames"))),
+            });
+        }
+
+        Ok(config)
+    }
+
+    pub fn framebuffer_size(&self, window_width: usize, window_height: usize) -> (usize, usize) {
+        (
+            ((window_width as f32 * self.render_scale).round() as usize).max(1),
+            ((window_height as f32 * self.render_scale).round() as usize).max(1),
+        )
+    }
+
+    fn load_file(&mut self, path: &str, record: &mut RecordArgs) -> Result<(), String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path, err))?;
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(format!("{}:{}: se esperaba clave = valor", path, number + 1))?;
+            self.apply(key.trim(), value.trim(), record)
+                .map_err(|err| format!("{}:{}: {}", path, number + 1, err))?;
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, key: &str, value: &str, record: &mut RecordArgs) -> Result<(), String> {
+        match key {
+            "size" => (self.width, self.height) = parse_size(key, value)?,
+            "width" => self.width = parse_positive(key, value)?,
+            "height" => self.height = parse_positive(key, value)?,
+            "render-scale" => {
+                let scale = parse_value::<f32>(key, value)?;
+                if !(0.1..=4.0).contains(&scale) {
+                    return Err(format!("{} debe estar entre 0.1 y 4.0", key));
+                }
+                self.render_scale = scale;
+            }
+            "window-scale" => {
+                self.window_scale = match value {
+                    "1" => Scale::X1,
+                    "2" => Scale::X2,
+                    "4" => Scale::X4,
+                    "8" => Scale::X8,
+                    "fit" => Scale::FitScreen,
+                    _ => return Err(format!("Valor inválido para {}: {} (usa 1, 2, 4, 8 o fit)", key, value)),
+                };
+            }
+            "resizable" => self.resizable = parse_value::<bool>(key, value)?,
+            "msaa" => {
+                self.msaa = match value {
+                    "1" => 1,
+                    "4" => 4,
+                    _ => return Err(format!("Valor inválido para {}: {} (usa 1 o 4)", key, value)),
+                };
+            }
+            "fxaa" => self.fxaa = parse_value::<bool>(key, value)?,
+            "scene" => self.scene = value.to_string(),
+            "seed" => self.seed = Some(parse_value::<u64>(key, value)?),
+            "date" => self.date = Some(ephemeris::parse_date(value)?),
+            "sphere" => {
+                self.sphere = match value {
+                    "ico" => SphereKind::Icosphere,
+                    "uv" => SphereKind::UvSphere,
+                    "obj" => SphereKind::Obj,
+                    _ => return Err(format!("Valor inválido para {}: {} (usa ico, uv u obj)", key, value)),
+                };
+            }
+            "lighting" => {
+                self.lighting = match value {
+                    "blinn-phong" => LightingModel::BlinnPhong,
+                    "pbr" => LightingModel::CookTorrance,
+                    _ => return Err(format!("Valor inválido para {}: {} (usa blinn-phong o pbr)", key, value)),
+                };
+            }
+            "lod-levels" => {
+                let levels = parse_value::<u32>(key, value)?;
+                if !(1..=7).contains(&levels) {
+                    return Err(format!("{} debe estar entre 1 y 7", key));
+                }
+                self.lod_levels = levels;
+            }
+            "print-scene" => self.print_scene = parse_value::<bool>(key, value)?,
+            "profile-csv" => self.profile_csv = Some(value.to_string()),
+            "record" => record.frames = Some(parse_value::<u32>(key, value)?),
+            "step" => record.step = Some(parse_value::<f32>(key, value)?),
+            "record-size" => record.size = Some(parse_size(key, value)?),
+            "output" => {
+                record.output = Some(if value == "-" {
+                    RecordOutput::Stdout
+                } else {
+                    RecordOutput::Images(PathBuf::from(value))
+                });
+            }
+            _ => return Err(format!("Opción desconocida: {}", key)),
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Valor inválido para {}: {}", key, value))
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<usize, String> {
+    match parse_value::<usize>(key, value)? {
+        0 => Err(format!("{} debe ser mayor que 0", key)),
+        n => Ok(n),
+    }
+}
+
+fn parse_size(key: &str, value: &str) -> Result<(usize, usize), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or(format!("Tamaño inválido para {}: {} (usa ANCHOxALTO)", key, value))?;
+    Ok((parse_positive(key, width)?, parse_positive(key, height)?))
+}
@@ -0,0 +1,274 @@
+use crate::planet::Planet;
+
+// Día juliano de la época J2000.0 (2000-01-01 12:00 TT)
+const J2000: f64 = 2_451_545.0;
+const DAYS_PER_CENTURY: f64 = 36_525.0;
+// Día juliano del 1970-01-01 00:00 UTC
+const UNIX_EPOCH: f64 = 2_440_587.5;
+// Unidades de escena por unidad astronómica: la Tierra queda a 25, como en la escena por defecto
+const AU_UNITS: f64 = 25.0;
+// Las distancias se comprimen (a^0.6) para que Mercurio y Neptuno quepan en la misma vista;
+// la forma, la orientación y la fase de cada órbita no cambian
+const DISTANCE_EXPONENT: f64 = 0.6;
+
+// Elementos keplerianos en J2000 y su variación por siglo juliano:
+// semieje mayor (ua), excentricidad, inclinación, longitud media, longitud del perihelio y
+// longitud del nodo ascendente (grados)
+struct Elements {
+    name: &'static str,
+    values: [f64; 6],
+    rates: [f64; 6],
+}
+
+// Tabla 1 de "Keplerian Elements for Approximate Positions of the Major Planets" (E. M. Standish,
+// JPL), válida entre 1800 y 2050. Para la Tierra se usa el baricentro Tierra-Luna
+const PLANETS: [Elements; 8] = [
+    Elements {
+        name: "Mercurio",
+        values: [0.38709927, 0.20563593, 7.00497902, 252.25032350, 77.45779628, 48.33076593],
+        rates: [0.00000037, 0.00001906, -0.00594749, 149472.67411175, 0.16047689, -0.12534081],
+    },
+    Elements {
+        name: "Venus",
+        values: [0.72333566, 0.00677672, 3.39467605, 181.97909950, 131.60246718, 76.67984255],
+        rates: [0.00000390, -0.00004107, -0.00078890, 58517.81538729, 0.00268329, -0.27769418],
+    },
+    Elements {
+        name: "Tierra",
+        values: [1.00000261, 0.01671123, -0.00001531, 100.46457166, 102.93768193, 0.0],
+        rates: [0.00000562, -0.00004392, -0.01294668, 35999.37244981, 0.32327364, 0.0],
+    },
+    Elements {
+        name: "Marte",
+        values: [1.52371034, 0.09339410, 1.84969142, -4.55343205, -23.94362959, 49.55953891],
+        rates: [0.00001847, 0.00007882, -0.00813131, 19140.30268499, 0.44441088, -0.29257343],
+    },
+    Elements {
+        name: "Júpiter",
+        values: [5.20288700, 0.04838624, 1.30439695, 34.39644051, 14.72847983, 100.47390909],
+        rates: [-0.00011607, -0.00013253, -0.00183714, 3034.74612775, 0.21252668, 0.20469106],
+    },
+    Elements {
+        name: "Saturno",
+        values: [9.53667594, 0.05386179, 2.48599187, 49.95424423, 92.59887831, 113.66242448],
+        rates: [-0.00125060, -0.00050991, 0.00193609, 1222.49362201, -0.41897216, -0.28867794],
+    },
+    Elements {
+        name: "Urano",
+        values: [19.18916464, 0.04725744, 0.77263783, 313.23810451, 170.95427630, 74.01692503],
+        rates: [-0.00196176, -0.00004397, -0.00242939, 428.48202785, 0.40805281, 0.04240589],
+    },
+    Elements {
+        name: "Neptuno",
+        values: [30.06992276, 0.00859048, 1.77004347, -55.12002969, 44.96476227, 131.78422574],
+        rates: [0.00026291, 0.00005105, 0.00035372, 218.45945325, -0.32241464, -0.00508664],
+    },
+];
+
+// Modo efemérides: los ocho planetas se colocan según la fecha y el reloj avanza en días
+pub struct Ephemeris {
+    pub julian_day: f64,
+}
+
+impl Ephemeris {
+    pub fn new(julian_day: f64) -> Self {
+        Ephemeris { julian_day }
+    }
+
+    // Una unidad de tiempo de simulación es un día
+    pub fn advance(&mut self, delta_time: f32) {
+        self.julian_day += delta_time as f64;
+    }
+
+    // Reemplaza la órbita de cada planeta de la tabla por la que tiene en la fecha actual.
+    // La anomalía media queda en la fecha y orbit_speed es el movimiento medio por día,
+    // así el paso de Planet::update_position lleva al cuerpo a la fecha siguiente
+    pub fn apply(&self, planets: &mut [Planet]) {
+        let centuries = (self.julian_day - J2000) / DAYS_PER_CENTURY;
+        for elements in PLANETS.iter() {
+            let planet = match planets.iter_mut().find(|planet| planet.name == elements.name) {
+                Some(planet) => planet,
+                None => continue,
+            };
+            let [a, e, inclination, mean_longitude, perihelion, node] = elements.at(centuries);
+            let mean_motion = (elements.rates[3] - elements.rates[4]) / DAYS_PER_CENTURY;
+
+            // Los elementos son heliocéntricos
+            planet.parent = None;
+            planet.orbit_radius = (AU_UNITS * a.powf(DISTANCE_EXPONENT)) as f32;
+            planet.orbit_speed = mean_motion.to_radians() as f32;
+            planet.current_angle = (mean_longitude - perihelion).rem_euclid(360.0).to_radians() as f32;
+            planet.eccentricity = e as f32;
+            planet.inclination = inclination as f32;
+            planet.periapsis = (perihelion - node).rem_euclid(360.0) as f32;
+            planet.ascending_node = node.rem_euclid(360.0) as f32;
+        }
+    }
+
+    // Fecha actual como AAAA-MM-DD HH:MM UTC
+    pub fn date(&self) -> String {
+        let minutes = ((self.julian_day - UNIX_EPOCH) * 1440.0).round() as i64;
+        let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+        let minute_of_day = minutes.rem_euclid(1440);
+        format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, minute_of_day / 60, minute_of_day % 60)
+    }
+}
+
+impl Elements {
+    fn at(&self, centuries: f64) -> [f64; 6] {
+        let mut values = self.values;
+        for (value, rate) in values.iter_mut().zip(self.rates) {
+            *value += rate * centuries;
+        }
+        values
+    }
+}
+
+// Planetas de la tabla que no están en la escena
+pub fn missing_planets(planets: &[Planet]) -> Vec<&'static str> {
+    PLANETS
+        .iter()
+        .map(|elements| elements.name)
+        .filter(|name| !planets.iter().any(|planet| planet.name == *name))
+        .collect()
+}
+
+// Lee AAAA-MM-DD o AAAA-MM-DDTHH:MM (UTC) y devuelve el día juliano
+pub fn parse_date(value: &str) -> Result<f64, String> {
+    let invalid = || format!("Fecha inválida: {} (usa AAAA-MM-DD o AAAA-MM-DDTHH:MM)", value);
+    let (date, time) = value.split_once('T').unwrap_or((value, "00:00"));
+
+    let mut fields = date.splitn(3, '-');
+    let mut field = || fields.next().and_then(|field| field.parse::<i64>().ok()).ok_or_else(invalid);
+    let (year, month, day) = (field()?, field()?, field()?);
+    let (hour, minute) = time.split_once(':').ok_or_else(invalid)?;
+    let (hour, minute) = (
+        hour.parse::<i64>().map_err(|_| invalid())?,
+        minute.parse::<i64>().map_err(|_| invalid())?,
+    );
+
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) || !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+        return Err(invalid());
+    }
+    Ok(UNIX_EPOCH + days_from_civil(year, month, day) as f64 + (hour * 60 + minute) as f64 / 1440.0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Días desde el 1970-01-01 en el calendario gregoriano (algoritmo de Howard Hinnant)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Scene;
+
+    #[test]
+    fn parses_dates_and_times() {
+        assert_eq!(parse_date("2000-01-01T12:00"), Ok(J2000));
+        assert_eq!(parse_date("2024-04-08T18:00"), Ok(2_460_409.25));
+        assert_eq!(parse_date("2024-04-08"), Ok(2_460_408.5));
+        assert_eq!(parse_date("1970-01-01"), Ok(UNIX_EPOCH));
+        assert_eq!(parse_date("2024-02-29T06:30"), Ok(2_460_369.5 + 0.270_833_333_333_333_3));
+        assert!(parse_date("2000-02-29").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for value in [
+            "",
+            "hola",
+            "2024-04",
+            "2024-4-8-1",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-13-01",
+            "2024-00-10",
+            "2024-04-31",
+            "2024-04-00",
+            "2024-04-08T24:00",
+            "2024-04-08T12:60",
+            "2024-04-08T12",
+            "2024-04-08T",
+            "2024-04-08 12:00",
+        ] {
+            assert!(parse_date(value).is_err(), "{} se aceptó", value);
+        }
+    }
+
+    // Cada día entre 1896 y 2104 (incluye 1900 y 2100, que no son bisiestos, y 2000, que sí)
+    #[test]
+    fn civil_dates_round_trip() {
+        let (start, end) = (days_from_civil(1896, 1, 1), days_from_civil(2104, 12, 31));
+        let mut previous = civil_from_days(start - 1);
+        for days in start..=end {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+            assert!((1..=days_in_month(year, month)).contains(&day));
+            // Días consecutivos: el siguiente al último del mes es el 1 del mes siguiente
+            if day == 1 {
+                assert_eq!(previous.2, days_in_month(previous.0, previous.1));
+            } else {
+                assert_eq!((year, month, day - 1), previous);
+            }
+            previous = (year, month, day);
+        }
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn formats_the_current_date() {
+        let mut ephemeris = Ephemeris::new(parse_date("2024-02-28T23:30").unwrap());
+        assert_eq!(ephemeris.date(), "2024-02-28 23:30 UTC");
+        ephemeris.advance(1.0);
+        assert_eq!(ephemeris.date(), "2024-02-29 23:30 UTC");
+        ephemeris.advance(0.25);
+        assert_eq!(ephemeris.date(), "2024-03-01 05:30 UTC");
+    }
+
+    // Longitud heliocéntrica de la Tierra el 2024-04-08 18:00 UTC según los elementos del JPL: ~199.06°
+    #[test]
+    fn places_the_earth_by_date() {
+        let mut scene = Scene::load("assets/scenes/ephemeris.scene").unwrap();
+        assert!(missing_planets(&scene.planets).is_empty());
+        Ephemeris::new(parse_date("2024-04-08T18:00").unwrap()).apply(&mut scene.planets);
+
+        let earth = scene.planets.iter().find(|planet| planet.name == "Tierra").unwrap();
+        let position = earth.orbit_point(earth.eccentric_anomaly());
+        let distance = position.norm() / AU_UNITS as f32;
+        assert!((0.98..1.02).contains(&distance), "distancia {} ua", distance);
+        // La eclíptica es el plano XZ: X apunta al equinoccio y la longitud crece hacia +Z
+        let longitude = position.z.atan2(position.x).to_degrees().rem_euclid(360.0);
+        assert!((longitude - 199.06).abs() < 0.1, "longitud {}°", longitude);
+        assert!(position.y.abs() < 0.01);
+    }
+}
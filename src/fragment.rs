@@ -1,25 +1,38 @@
 
-use nalgebra_glm::{Vec2, Vec3};
-use crate::color::Color;
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 
 pub struct Fragment {
     pub position: Vec2,
-    pub color: Color,
-    pub depth: f32,
     pub normal: Vec3,
-    pub intensity: f32,
     pub vertex_position: Vec3,
+    pub tex_coords: Vec2,
+    pub tangent: Vec4,
+    pub coverage: u8,
+    pub sample_depths: [f32; 4],
 }
 
 impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, intensity: f32, vertex_position: Vec3,) -> Self {
+    pub fn new(x: f32, y: f32, depth: f32, normal: Vec3, vertex_position: Vec3) -> Self {
         Fragment {
             position: Vec2::new(x, y),
-            color,
-            depth,
             normal,
-            intensity,
-            vertex_position
+            vertex_position,
+            tex_coords: Vec2::zeros(),
+            tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            coverage: 1,
+            sample_depths: [depth; 4],
         }
     }
+
+    pub fn with_surface(mut self, tex_coords: Vec2, tangent: Vec4) -> Self {
+        self.tex_coords = tex_coords;
+        self.tangent = tangent;
+        self
+    }
+
+    pub fn with_coverage(mut self, coverage: u8, sample_depths: [f32; 4]) -> Self {
+        self.coverage = coverage;
+        self.sample_depths = sample_depths;
+        self
+    }
 }
\ No newline at end of file
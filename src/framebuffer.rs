@@ -1,23 +1,40 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+
+pub const NO_OBJECT: u32 = u32::MAX;
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    pub samples: usize,
     pub buffer: Vec<u32>,         
     pub zbuffer: Vec<f32>,        
+    pub sample_buffer: Vec<u32>,
+    pub object_buffer: Vec<u32>,
     pub emission_buffer: Vec<u32>, 
     background_color: u32,
     current_color: u32,
+    current_object: u32,
 }
 
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::with_samples(width, height, 1)
+    }
+
+    pub fn with_samples(width: usize, height: usize, samples: usize) -> Self {
         Framebuffer {
             width,
             height,
+            samples,
             buffer: vec![0; width * height],                 
-            zbuffer: vec![f32::INFINITY; width * height],     
+            zbuffer: vec![f32::INFINITY; width * height * samples],     
+            sample_buffer: vec![0; width * height * samples],
+            object_buffer: vec![NO_OBJECT; width * height * samples],
             emission_buffer: vec![0; width * height],         
             background_color: 0x000000,                       
             current_color: 0xFFFFFF,                          
+            current_object: NO_OBJECT,
         }
     }
 
@@ -25,9 +42,15 @@ impl Framebuffer {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
         }
+        for sample in self.sample_buffer.iter_mut() {
+            *sample = self.background_color;
+        }
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        for object in self.object_buffer.iter_mut() {
+            *object = NO_OBJECT;
+        }
         for emission in self.emission_buffer.iter_mut() {
             *emission = 0;
         }
@@ -41,15 +64,250 @@ impl Framebuffer {
         self.current_color = color;
     }
 
-    pub fn point_with_emission(&mut self, x: usize, y: usize, depth: f32, emission: u32) {
+    pub fn set_current_object(&mut self, object: u32) {
+        self.current_object = object;
+    }
+
+    // Escribe el color actual en las muestras cubiertas por `coverage` que pasen la prueba de profundidad
+    pub fn point_samples(&mut self, x: usize, y: usize, coverage: u8, depths: &[f32], emission: u32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
+            let base = index * self.samples;
+            let mut written = false;
 
-            if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;  
-                self.emission_buffer[index] = emission;   
-                self.zbuffer[index] = depth;              
+            for (sample, depth) in depths.iter().enumerate().take(self.samples) {
+                if coverage & (1 << sample) != 0 && self.zbuffer[base + sample] > *depth {
+                    self.sample_buffer[base + sample] = self.current_color;
+                    self.object_buffer[base + sample] = self.current_object;
+                    self.zbuffer[base + sample] = *depth;
+                    written = true;
+                }
             }
+
+            if written {
+                self.emission_buffer[index] = emission;
+            }
+        }
+    }
+
+    // Objeto visible en el píxel: el de la muestra más cercana
+    pub fn object_at(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
         }
+        let base = (y * self.width + x) * self.samples;
+        (base..base + self.samples)
+            .min_by(|a, b| self.zbuffer[*a].total_cmp(&self.zbuffer[*b]))
+            .map(|sample| self.object_buffer[sample])
+            .filter(|object| *object != NO_OBJECT)
     }
-}
\ No newline at end of file
+
+    // Dibuja el contorno de un objeto sobre el buffer resuelto
+    pub fn outline_object(&mut self, object: u32, color: u32) {
+        let (width, height) = (self.width, self.height);
+        let objects: Vec<Option<u32>> = (0..width * height)
+            .into_par_iter()
+            .map(|index| self.object_at(index % width, index / width))
+            .collect();
+
+        self.buffer
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    if objects[y * width + x] == Some(object) {
+                        continue;
+                    }
+                    let touches_object = (y.saturating_sub(1)..(y + 2).min(height)).any(|ny| {
+                        (x.saturating_sub(1)..(x + 2).min(width)).any(|nx| objects[ny * width + nx] == Some(object))
+                    });
+                    if touches_object {
+                        *pixel = color;
+                    }
+                }
+            });
+    }
+
+    pub fn covered_pixels(&self) -> usize {
+        self.zbuffer
+            .par_chunks(self.samples)
+            .filter(|depths| depths.iter().any(|depth| depth.is_finite()))
+            .count()
+    }
+
+    pub fn resolve(&mut self) {
+        let samples = self.samples;
+        if samples == 1 {
+            self.buffer.copy_from_slice(&self.sample_buffer);
+            return;
+        }
+
+        self.buffer
+            .par_iter_mut()
+            .zip(self.sample_buffer.par_chunks(samples))
+            .for_each(|(pixel, colors)| {
+                let mut sum = [0u32; 3];
+                for color in colors {
+                    sum[0] += (color >> 16) & 0xFF;
+                    sum[1] += (color >> 8) & 0xFF;
+                    sum[2] += color & 0xFF;
+                }
+                let count = samples as u32;
+                *pixel = ((sum[0] / count) << 16) | ((sum[1] / count) << 8) | (sum[2] / count);
+            });
+    }
+
+    // Oscurece un rectángulo del buffer resuelto para dibujar paneles encima de la escena
+    pub fn blend_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32, alpha: f32) {
+        let x_end = (x + width).min(self.width);
+        let y_end = (y + height).min(self.height);
+        for py in y.min(y_end)..y_end {
+            for pixel in &mut self.buffer[py * self.width + x.min(x_end)..py * self.width + x_end] {
+                *pixel = blend_color(*pixel, color, alpha);
+            }
+        }
+    }
+
+    // Mezcla sobre el buffer resuelto solo si no hay geometría más cercana en el píxel
+    pub fn blend_point_depth(&mut self, x: usize, y: usize, depth: f32, color: u32, alpha: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        if depth < self.nearest_depth(x, y) {
+            let pixel = &mut self.buffer[y * self.width + x];
+            *pixel = blend_color(*pixel, color, alpha);
+        }
+    }
+
+    // Suma un disco difuso sobre el buffer resuelto (mezcla aditiva) centrado en (x, y) con su profundidad.
+    // Un disco de menos de un píxel aporta en proporción a su área
+    pub fn add_splat(&mut self, center: Vec3, radius: f32, color: u32, intensity: f32) {
+        if radius < 1.0 {
+            if center.x >= 0.0 && center.y >= 0.0 {
+                let area = (std::f32::consts::PI * radius * radius).min(1.0);
+                self.add_point_depth(center.x as usize, center.y as usize, center.z, color, intensity * area);
+            }
+            return;
+        }
+        let x_start = (center.x - radius).floor().max(0.0) as usize;
+        let y_start = (center.y - radius).floor().max(0.0) as usize;
+        let x_end = ((center.x + radius).ceil().max(0.0) as usize).min(self.width);
+        let y_end = ((center.y + radius).ceil().max(0.0) as usize).min(self.height);
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let dx = x as f32 + 0.5 - center.x;
+                let dy = y as f32 + 0.5 - center.y;
+                let falloff = 1.0 - (dx * dx + dy * dy) / (radius * radius);
+                if falloff > 0.0 {
+                    self.add_point_depth(x, y, center.z, color, intensity * falloff);
+                }
+            }
+        }
+    }
+
+    fn add_point_depth(&mut self, x: usize, y: usize, depth: f32, color: u32, intensity: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        if depth < self.nearest_depth(x, y) {
+            let pixel = &mut self.buffer[y * self.width + x];
+            *pixel = add_color(*pixel, color, intensity);
+        }
+    }
+
+    fn nearest_depth(&self, x: usize, y: usize) -> f32 {
+        let base = (y * self.width + x) * self.samples;
+        self.zbuffer[base..base + self.samples].iter().copied().fold(f32::INFINITY, f32::min)
+    }
+
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.buffer.len() * 3);
+        for pixel in &self.buffer {
+            bytes.push((pixel >> 16) as u8);
+            bytes.push((pixel >> 8) as u8);
+            bytes.push(*pixel as u8);
+        }
+        bytes
+    }
+
+    pub fn resolve_into(&self, target: &mut [u32], target_width: usize, target_height: usize) {
+        if self.width == target_width && self.height == target_height {
+            target.copy_from_slice(&self.buffer);
+            return;
+        }
+
+        let scale_x = self.width as f32 / target_width as f32;
+        let scale_y = self.height as f32 / target_height as f32;
+
+        target
+            .par_chunks_mut(target_width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    *pixel = if scale_x >= 1.0 && scale_y >= 1.0 {
+                        self.box_filter(x as f32 * scale_x, y as f32 * scale_y, scale_x, scale_y)
+                    } else {
+                        self.bilinear((x as f32 + 0.5) * scale_x - 0.5, (y as f32 + 0.5) * scale_y - 0.5)
+                    };
+                }
+            });
+    }
+
+    fn box_filter(&self, x0: f32, y0: f32, size_x: f32, size_y: f32) -> u32 {
+        let x_start = x0 as usize;
+        let y_start = y0 as usize;
+        let x_end = ((x0 + size_x).ceil() as usize).min(self.width).max(x_start + 1);
+        let y_end = ((y0 + size_y).ceil() as usize).min(self.height).max(y_start + 1);
+
+        let mut sum = [0u32; 3];
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let pixel = self.buffer[y * self.width + x];
+                sum[0] += (pixel >> 16) & 0xFF;
+                sum[1] += (pixel >> 8) & 0xFF;
+                sum[2] += pixel & 0xFF;
+            }
+        }
+
+        let count = ((x_end - x_start) * (y_end - y_start)) as u32;
+        ((sum[0] / count) << 16) | ((sum[1] / count) << 8) | (sum[2] / count)
+    }
+
+    fn bilinear(&self, x: f32, y: f32) -> u32 {
+        let x = x.clamp(0.0, (self.width - 1) as f32);
+        let y = y.clamp(0.0, (self.height - 1) as f32);
+        let x0 = x.floor() as usize;
+        let y0 = y.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let tx = x - x0 as f32;
+        let ty = y - y0 as f32;
+
+        let sample = |px: usize, py: usize, shift: u32| ((self.buffer[py * self.width + px] >> shift) & 0xFF) as f32;
+        let channel = |shift: u32| {
+            let top = sample(x0, y0, shift) * (1.0 - tx) + sample(x1, y0, shift) * tx;
+            let bottom = sample(x0, y1, shift) * (1.0 - tx) + sample(x1, y1, shift) * tx;
+            (top * (1.0 - ty) + bottom * ty).round() as u32
+        };
+
+        (channel(16) << 16) | (channel(8) << 8) | channel(0)
+    }
+}
+
+fn add_color(base: u32, added: u32, intensity: f32) -> u32 {
+    let add = |shift: u32| {
+        let base = ((base >> shift) & 0xFF) as f32;
+        let added = ((added >> shift) & 0xFF) as f32;
+        (base + added * intensity).round().min(255.0) as u32
+    };
+    (add(16) << 16) | (add(8) << 8) | add(0)
+}
+
+fn blend_color(base: u32, over: u32, alpha: f32) -> u32 {
+    let blend = |shift: u32| {
+        let base = ((base >> shift) & 0xFF) as f32;
+        let over = ((over >> shift) & 0xFF) as f32;
+        (base + (over - base) * alpha).round() as u32
+    };
+    (blend(16) << 16) | (blend(8) << 8) | blend(0)
+}
@@ -0,0 +1,87 @@
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+
+const EDGE_THRESHOLD: f32 = 0.125;
+const EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const SUBPIXEL_QUALITY: f32 = 0.75;
+// Sin búsqueda del extremo del borde se usa una mezcla mínima constante
+const EDGE_BLEND: f32 = 0.25;
+
+fn luma(pixel: u32) -> f32 {
+    let r = ((pixel >> 16) & 0xFF) as f32;
+    let g = ((pixel >> 8) & 0xFF) as f32;
+    let b = (pixel & 0xFF) as f32;
+    (0.299 * r + 0.587 * g + 0.114 * b) / 255.0
+}
+
+fn blend(a: u32, b: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let ca = ((a >> shift) & 0xFF) as f32;
+        let cb = ((b >> shift) & 0xFF) as f32;
+        (ca + (cb - ca) * t).round() as u32
+    };
+    (channel(16) << 16) | (channel(8) << 8) | channel(0)
+}
+
+// FXAA simplificado: detecta bordes por contraste de luminancia y mezcla con el vecino a través del borde
+pub fn apply(framebuffer: &mut Framebuffer) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    if width < 3 || height < 3 {
+        return;
+    }
+
+    let source = framebuffer.buffer.clone();
+    let lumas: Vec<f32> = source.par_iter().map(|pixel| luma(*pixel)).collect();
+
+    framebuffer
+        .buffer
+        .par_chunks_mut(width)
+        .enumerate()
+        .skip(1)
+        .take(height - 2)
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                let index = y * width + x;
+                let m = lumas[index];
+                let n = lumas[index - width];
+                let s = lumas[index + width];
+                let w = lumas[index - 1];
+                let e = lumas[index + 1];
+
+                let max = m.max(n).max(s).max(w).max(e);
+                let min = m.min(n).min(s).min(w).min(e);
+                let range = max - min;
+                if range < EDGE_THRESHOLD_MIN.max(max * EDGE_THRESHOLD) {
+                    continue;
+                }
+
+                let nw = lumas[index - width - 1];
+                let ne = lumas[index - width + 1];
+                let sw = lumas[index + width - 1];
+                let se = lumas[index + width + 1];
+
+                let edge_horizontal = (n + s - 2.0 * m).abs() * 2.0
+                    + (nw + sw - 2.0 * w).abs()
+                    + (ne + se - 2.0 * e).abs();
+                let edge_vertical = (w + e - 2.0 * m).abs() * 2.0
+                    + (nw + ne - 2.0 * n).abs()
+                    + (sw + se - 2.0 * s).abs();
+
+                let neighbor = if edge_horizontal >= edge_vertical {
+                    if (n - m).abs() >= (s - m).abs() { index - width } else { index + width }
+                } else if (w - m).abs() >= (e - m).abs() {
+                    index - 1
+                } else {
+                    index + 1
+                };
+
+                let average = (n + s + w + e) * 0.25;
+                let subpixel = ((average - m).abs() / range).clamp(0.0, 1.0);
+                let subpixel = subpixel * subpixel * (3.0 - 2.0 * subpixel);
+                let amount = subpixel * subpixel * SUBPIXEL_QUALITY;
+
+                *pixel = blend(source[index], source[neighbor], amount.max(EDGE_BLEND));
+            }
+        });
+}
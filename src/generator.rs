@@ -0,0 +1,565 @@
+use std::f32::consts::PI;
+use std::fmt::Write;
+use crate::color::Color;
+use crate::shaders::ShaderParam;
+
+// Órbita y velocidad de referencia (la Tierra en la escena por defecto); el resto sigue la tercera ley de Kepler
+const REFERENCE_ORBIT: f32 = 25.0;
+const REFERENCE_SPEED: f32 = 0.001;
+// Ninguna órbita planetaria pasa de este radio, para que el sistema quepa en la vista inicial
+const MAX_ORBIT: f32 = 220.0;
+const MAX_PLANETS: usize = 8;
+// Velocidad de una luna a dos unidades de su planeta
+const MOON_REFERENCE_ORBIT: f32 = 2.0;
+const MOON_REFERENCE_SPEED: f32 = 0.01;
+const PLANET_LETTERS: [&str; MAX_PLANETS] = ["b", "c", "d", "e", "f", "g", "h", "i"];
+const MOON_NUMERALS: [&str; 4] = ["I", "II", "III", "IV"];
+const MAX_DWARF_PLANETS: usize = 2;
+const MAX_COMETS: usize = 2;
+// El afelio de un cometa queda como mínimo a este múltiplo del perihelio, aunque el sistema sea compacto
+const MIN_COMET_APHELION_RATIO: f32 = 4.0;
+const MAX_COMET_ECCENTRICITY: f32 = 0.95;
+const SYLLABLES: [&str; 16] = [
+    "ka", "ra", "ven", "tor", "li", "mi", "sa", "dor", "qua", "nel", "ze", "phi", "ar", "on", "te", "lux",
+];
+
+// Generador SplitMix64: sin dependencias y con la misma secuencia en cualquier plataforma
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Valor en 0..1 con los 24 bits que caben exactos en un f32
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    pub fn below(&mut self, count: usize) -> usize {
+        (self.next_u64() % count as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    // Escala cada canal por separado para que dos cuerpos con la misma paleta no sean idénticos
+    fn vary(&mut self, color: Color, amount: f32) -> Color {
+        let [r, g, b] = color.to_rgb_f32();
+        let mut channel = |value: f32| (value * self.range(1.0 - amount, 1.0 + amount)).clamp(0.0, 1.0);
+        Color::from_rgb_f32([channel(r), channel(g), channel(b)])
+    }
+}
+
+// Tipo espectral: colores del shader sun, tamaño y luminosidad relativa al Sol.
+// La luminosidad está comprimida para que las zonas de todas las estrellas quepan en la escena
+struct StarClass {
+    name: char,
+    weight: f32,
+    core: Color,
+    flare: Color,
+    corona: Color,
+    halo: Color,
+    size: f32,
+    luminosity: f32,
+}
+
+const STAR_CLASSES: [StarClass; 7] = [
+    StarClass {
+        name: 'O',
+        weight: 0.01,
+        core: Color::new(150, 180, 255),
+        flare: Color::new(90, 120, 255),
+        corona: Color::new(220, 230, 255),
+        halo: Color::new(170, 200, 255),
+        size: 2.6,
+        luminosity: 6.0,
+    },
+    StarClass {
+        name: 'B',
+        weight: 0.03,
+        core: Color::new(170, 195, 255),
+        flare: Color::new(110, 140, 255),
+        corona: Color::new(230, 240, 255),
+        halo: Color::new(190, 210, 255),
+        size: 2.2,
+        luminosity: 4.0,
+    },
+    StarClass {
+        name: 'A',
+        weight: 0.06,
+        core: Color::new(220, 225, 255),
+        flare: Color::new(170, 180, 255),
+        corona: Color::new(255, 255, 255),
+        halo: Color::new(230, 235, 255),
+        size: 1.9,
+        luminosity: 2.2,
+    },
+    StarClass {
+        name: 'F',
+        weight: 0.12,
+        core: Color::new(255, 245, 220),
+        flare: Color::new(255, 200, 120),
+        corona: Color::new(255, 255, 230),
+        halo: Color::new(255, 240, 190),
+        size: 1.7,
+        luminosity: 1.5,
+    },
+    StarClass {
+        name: 'G',
+        weight: 0.2,
+        core: Color::new(255, 140, 0),
+        flare: Color::new(255, 69, 0),
+        corona: Color::new(255, 255, 160),
+        halo: Color::new(255, 215, 0),
+        size: 1.5,
+        luminosity: 1.0,
+    },
+    StarClass {
+        name: 'K',
+        weight: 0.25,
+        core: Color::new(255, 110, 20),
+        flare: Color::new(220, 50, 0),
+        corona: Color::new(255, 200, 120),
+        halo: Color::new(255, 160, 60),
+        size: 1.3,
+        luminosity: 0.6,
+    },
+    StarClass {
+        name: 'M',
+        weight: 0.33,
+        core: Color::new(230, 60, 20),
+        flare: Color::new(160, 20, 0),
+        corona: Color::new(255, 140, 90),
+        halo: Color::new(255, 90, 40),
+        size: 1.0,
+        luminosity: 0.3,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlanetClass {
+    Rocky,
+    GasGiant,
+    Ice,
+}
+
+// Paletas de cuatro colores: de la más clara a la más oscura
+const ROCK_PALETTES: [[Color; 4]; 4] = [
+    // Óxido, como Marte
+    [Color::new(210, 77, 38), Color::new(150, 75, 45), Color::new(105, 54, 36), Color::new(130, 60, 35)],
+    // Roca desnuda, como Mercurio
+    [Color::new(160, 150, 140), Color::new(115, 110, 105), Color::new(80, 76, 72), Color::new(125, 120, 112)],
+    // Azufre, como Ío
+    [Color::new(225, 205, 95), Color::new(180, 140, 60), Color::new(120, 90, 40), Color::new(200, 170, 80)],
+    // Basalto oscuro
+    [Color::new(95, 85, 80), Color::new(70, 62, 58), Color::new(45, 40, 38), Color::new(85, 75, 68)],
+];
+const ICE_ROCK_PALETTE: [Color; 4] =
+    [Color::new(225, 232, 240), Color::new(170, 180, 195), Color::new(120, 128, 145), Color::new(190, 200, 215)];
+const GAS_PALETTES: [[Color; 4]; 4] = [
+    [Color::new(255, 239, 170), Color::new(230, 220, 170), Color::new(180, 120, 70), Color::new(120, 70, 40)],
+    [Color::new(240, 220, 160), Color::new(210, 180, 120), Color::new(170, 130, 80), Color::new(110, 85, 55)],
+    [Color::new(250, 210, 190), Color::new(220, 160, 130), Color::new(170, 90, 70), Color::new(110, 50, 40)],
+    [Color::new(235, 235, 215), Color::new(205, 200, 175), Color::new(160, 150, 120), Color::new(120, 110, 90)],
+];
+const ICE_GIANT_PALETTES: [[Color; 4]; 2] = [
+    [Color::new(200, 230, 255), Color::new(150, 200, 240), Color::new(90, 140, 210), Color::new(60, 90, 170)],
+    [Color::new(210, 245, 240), Color::new(160, 220, 220), Color::new(100, 180, 195), Color::new(70, 130, 160)],
+];
+// Hielo, escarcha, polvo y material oscuro del shader icy
+const DWARF_PALETTES: [[Color; 4]; 2] = [
+    // Hielo de nitrógeno con tolinas rojizas, como Plutón
+    [Color::new(235, 225, 215), Color::new(205, 190, 175), Color::new(175, 125, 95), Color::new(95, 60, 45)],
+    // Hielo de agua grisáceo, como Ceres
+    [Color::new(210, 215, 220), Color::new(170, 175, 185), Color::new(110, 105, 100), Color::new(60, 58, 56)],
+];
+const COMET_PALETTE: [Color; 4] =
+    [Color::new(220, 230, 240), Color::new(170, 185, 200), Color::new(75, 70, 65), Color::new(30, 28, 27)];
+const LAND_COLORS: [Color; 4] = [
+    Color::new(34, 139, 34),
+    Color::new(60, 120, 60),
+    Color::new(110, 130, 50),
+    Color::new(90, 60, 110),
+];
+
+// Genera un sistema estelar completo como texto de escena; la misma semilla da siempre el mismo sistema
+pub fn generate(seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut scene = SceneWriter::default();
+
+    let star = weighted_star(&mut rng);
+    let star_name = star_name(&mut rng);
+    let zone_scale = star.luminosity.sqrt();
+    let habitable = (20.0 * zone_scale, 34.0 * zone_scale);
+    let frost_line = 50.0 * zone_scale;
+
+    writeln!(scene.text, "# Sistema generado con --seed {}", seed).unwrap();
+    writeln!(scene.text, "# Estrella de tipo {}; línea de hielo en {:.0}", star.name, frost_line).unwrap();
+
+    scene.body(&star_name, None, 0.0, 0.0, star.size);
+    writeln!(scene.text, "particles = flares").unwrap();
+    scene.shader("sun");
+    scene.color("core_color", star.core);
+    scene.color("flare_color", star.flare);
+    scene.color("corona_color", star.corona);
+    scene.color("halo_color", star.halo);
+
+    // Espaciado de Titius-Bode generalizado: cada órbita es la anterior por un factor casi constante
+    let ratio = rng.range(1.4, 1.8);
+    let mut orbit = star.size * 8.0 + rng.range(4.0, 10.0);
+    let planets = 3 + rng.below(MAX_PLANETS - 2);
+    let mut previous_orbit = None;
+    let mut has_belt = false;
+
+    for letter in PLANET_LETTERS.iter().take(planets) {
+        if orbit > MAX_ORBIT {
+            break;
+        }
+        let name = format!("{} {}", star_name, letter);
+        let class = planet_class(&mut rng, orbit, frost_line);
+        // Como entre Marte y Júpiter: un cinturón en el hueco antes del primer gigante pasada la línea de hielo
+        if let Some(previous) = previous_orbit.filter(|_| class == PlanetClass::GasGiant && orbit > frost_line && !has_belt) {
+            has_belt = true;
+            if rng.chance(0.7) {
+                asteroid_belt(&mut scene, &mut rng, &star_name, previous, orbit);
+            }
+        }
+        let size = match class {
+            PlanetClass::Rocky => rng.range(0.3, 0.7),
+            PlanetClass::GasGiant => rng.range(0.9, 1.5),
+            PlanetClass::Ice => rng.range(0.6, 0.9),
+        };
+        let ring_chance = match class {
+            PlanetClass::Rocky => 0.0,
+            PlanetClass::GasGiant => 0.35,
+            PlanetClass::Ice => 0.25,
+        };
+        let rings = rng.chance(ring_chance).then(|| {
+            let inner = rng.range(0.6, 0.75);
+            (inner, inner + rng.range(0.3, 0.6))
+        });
+
+        scene.body(&name, None, orbit, kepler_speed(orbit, REFERENCE_ORBIT, REFERENCE_SPEED), size);
+        if let Some((inner, outer)) = rings {
+            writeln!(scene.text, "rings = {}, {}", round(inner), round(outer)).unwrap();
+        }
+        match class {
+            PlanetClass::Rocky if (habitable.0..habitable.1).contains(&orbit) => earth_like(&mut scene, &mut rng),
+            PlanetClass::Rocky => {
+                let palette = rock_palette(&mut rng, orbit, habitable.0);
+                rocky(&mut scene, &mut rng, palette)
+            }
+            PlanetClass::GasGiant | PlanetClass::Ice => {
+                let palette = match class {
+                    PlanetClass::Ice => *rng.pick(&ICE_GIANT_PALETTES),
+                    _ => *rng.pick(&GAS_PALETTES),
+                };
+                match rings {
+                    Some((inner, outer)) => ringed_giant(&mut scene, &mut rng, palette, inner, outer),
+                    None => banded_giant(&mut scene, &mut rng, palette, class),
+                }
+            }
+        }
+
+        let moons = match class {
+            PlanetClass::Rocky => usize::from(rng.chance(0.3)),
+            PlanetClass::GasGiant => 1 + rng.below(4),
+            PlanetClass::Ice => rng.below(3),
+        };
+        // Las lunas empiezan fuera de los anillos y se separan de forma regular
+        let mut moon_orbit = size * rings.map_or(1.5, |(_, outer)| outer + 0.4);
+        for numeral in MOON_NUMERALS.iter().take(moons) {
+            moon_orbit += size * rng.range(0.5, 1.0);
+            let moon_name = format!("{} {}", name, numeral);
+            let moon_speed = kepler_speed(moon_orbit, MOON_REFERENCE_ORBIT, MOON_REFERENCE_SPEED);
+            scene.body(&moon_name, Some(&name), moon_orbit, moon_speed, rng.range(0.08, 0.25));
+            let palette = if orbit > frost_line { ICE_ROCK_PALETTE } else { ROCK_PALETTES[1] };
+            rocky(&mut scene, &mut rng, palette);
+        }
+
+        previous_orbit = Some(orbit);
+        orbit *= ratio * rng.range(0.92, 1.08);
+    }
+
+    // Cuerpos menores: planetas enanos más allá del último planeta y cometas que caen desde ahí
+    // hasta dentro de la línea de hielo
+    let outermost = previous_orbit.unwrap_or(orbit);
+    for _ in 0..rng.below(MAX_DWARF_PLANETS + 1) {
+        dwarf_planet(&mut scene, &mut rng, outermost);
+    }
+    for _ in 0..1 + rng.below(MAX_COMETS) {
+        comet(&mut scene, &mut rng, outermost, frost_line);
+    }
+
+    scene.text
+}
+
+// Semilla del ruido de los shaders derivada de la del sistema
+pub fn noise_seed(seed: u64) -> i32 {
+    Rng::new(seed).next_u64() as i32
+}
+
+fn weighted_star(rng: &mut Rng) -> &'static StarClass {
+    let total: f32 = STAR_CLASSES.iter().map(|star| star.weight).sum();
+    let mut target = rng.range(0.0, total);
+    for star in &STAR_CLASSES {
+        if target < star.weight {
+            return star;
+        }
+        target -= star.weight;
+    }
+    &STAR_CLASSES[STAR_CLASSES.len() - 1]
+}
+
+fn star_name(rng: &mut Rng) -> String {
+    let syllables = 2 + rng.below(2);
+    let name: String = (0..syllables).map(|_| *rng.pick(&SYLLABLES)).collect();
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+// Dentro de la línea de hielo dominan los planetas rocosos (con algún júpiter caliente);
+// justo después se forman los gigantes gaseosos y más lejos los de hielo
+fn planet_class(rng: &mut Rng, orbit: f32, frost_line: f32) -> PlanetClass {
+    let gas_chance = if orbit < frost_line {
+        0.08
+    } else if orbit < frost_line * 2.5 {
+        0.7
+    } else {
+        0.3
+    };
+    if rng.chance(gas_chance) {
+        PlanetClass::GasGiant
+    } else if orbit < frost_line {
+        PlanetClass::Rocky
+    } else {
+        PlanetClass::Ice
+    }
+}
+
+fn kepler_speed(orbit: f32, reference_orbit: f32, reference_speed: f32) -> f32 {
+    reference_speed * (reference_orbit / orbit).powf(1.5)
+}
+
+// Los planetas más cercanos que la zona habitable quedan calcinados
+fn rock_palette(rng: &mut Rng, orbit: f32, habitable_start: f32) -> [Color; 4] {
+    if orbit < habitable_start * 0.7 {
+        ROCK_PALETTES[1]
+    } else {
+        *rng.pick(&ROCK_PALETTES)
+    }
+}
+
+fn earth_like(scene: &mut SceneWriter, rng: &mut Rng) {
+    scene.shader("earth");
+    scene.color("ocean_color", rng.vary(Color::new(0, 102, 204), 0.25));
+    let land = *rng.pick(&LAND_COLORS);
+    scene.color("land_color", rng.vary(land, 0.2));
+    scene.number("terrain_scale", rng.range(4.0, 8.0));
+    scene.number("ocean_threshold", rng.range(-0.15, 0.25));
+    scene.number("cloud_threshold", rng.range(-0.3, 0.2));
+    scene.number("ice_threshold", rng.range(0.6, 0.9));
+}
+
+fn rocky(scene: &mut SceneWriter, rng: &mut Rng, palette: [Color; 4]) {
+    scene.shader("mars");
+    writeln!(scene.text, "vertex_shader = terrain").unwrap();
+    for (key, color) in ["sand_color", "rock_color", "crater_color", "ridge_color"].iter().zip(palette) {
+        scene.color(key, rng.vary(color, 0.06));
+    }
+    scene.number("crater_scale", rng.range(3.0, 8.0));
+    scene.number("rock_threshold", rng.range(0.45, 0.7));
+    scene.number("displacement_height", rng.range(0.02, 0.05));
+}
+
+fn dwarf_planet(scene: &mut SceneWriter, rng: &mut Rng, outermost: f32) {
+    let orbit = (outermost * rng.range(1.15, 1.4)).min(MAX_ORBIT);
+    let name = star_name(rng);
+    scene.body(&name, None, orbit, kepler_speed(orbit, REFERENCE_ORBIT, REFERENCE_SPEED), rng.range(0.15, 0.3));
+    let eccentricity = rng.range(0.1, 0.3);
+    scene.orbit(eccentricity, rng.range(5.0, 20.0), rng);
+    let palette = *rng.pick(&DWARF_PALETTES);
+    let ice_coverage = rng.range(0.5, 0.85);
+    icy(scene, rng, palette, ice_coverage);
+}
+
+// Órbita muy alargada: el perihelio queda dentro de la línea de hielo, donde la cola se activa
+fn comet(scene: &mut SceneWriter, rng: &mut Rng, outermost: f32, frost_line: f32) {
+    let perihelion = frost_line * rng.range(0.3, 0.8);
+    let aphelion = outermost.max(perihelion * MIN_COMET_APHELION_RATIO) * rng.range(1.0, 1.5);
+    let orbit = (perihelion + aphelion) / 2.0;
+    let name = format!("Cometa {}", star_name(rng));
+    scene.body(&name, None, orbit, kepler_speed(orbit, REFERENCE_ORBIT, REFERENCE_SPEED), rng.range(0.08, 0.15));
+    let eccentricity = ((aphelion - perihelion) / (aphelion + perihelion)).min(MAX_COMET_ECCENTRICITY);
+    scene.orbit(eccentricity, rng.range(0.0, 70.0), rng);
+    writeln!(scene.text, "particles = tail").unwrap();
+    scene.number("activity_distance", frost_line);
+    let ice_coverage = rng.range(0.1, 0.25);
+    icy(scene, rng, COMET_PALETTE, ice_coverage);
+}
+
+fn icy(scene: &mut SceneWriter, rng: &mut Rng, palette: [Color; 4], ice_coverage: f32) {
+    scene.shader("icy");
+    writeln!(scene.text, "vertex_shader = terrain").unwrap();
+    for (key, color) in ["ice_color", "frost_color", "dust_color", "dark_color"].iter().zip(palette) {
+        scene.color(key, rng.vary(color, 0.06));
+    }
+    scene.number("ice_coverage", ice_coverage);
+    scene.number("crater_scale", rng.range(3.0, 6.0));
+    scene.number("displacement_height", rng.range(0.04, 0.08));
+}
+
+fn asteroid_belt(scene: &mut SceneWriter, rng: &mut Rng, star_name: &str, inner_orbit: f32, outer_orbit: f32) {
+    let orbit = (inner_orbit * outer_orbit).sqrt();
+    scene.body(
+        &format!("Cinturón de {}", star_name),
+        None,
+        orbit,
+        kepler_speed(orbit, REFERENCE_ORBIT, REFERENCE_SPEED),
+        rng.range(0.08, 0.15),
+    );
+    writeln!(scene.text, "asteroids = {}", 1500 + rng.below(1500)).unwrap();
+    scene.number("belt_width", (outer_orbit - inner_orbit) * 0.7);
+    writeln!(scene.text, "seed = {}", rng.next_u64()).unwrap();
+    scene.shader("mars");
+    writeln!(scene.text, "vertex_shader = standard").unwrap();
+    let palette = *rng.pick(&[ROCK_PALETTES[1], ROCK_PALETTES[3]]);
+    for (key, color) in ["sand_color", "rock_color", "crater_color", "ridge_color"].iter().zip(palette) {
+        scene.color(key, rng.vary(color, 0.06));
+    }
+}
+
+fn banded_giant(scene: &mut SceneWriter, rng: &mut Rng, palette: [Color; 4], class: PlanetClass) {
+    scene.shader("jupiter");
+    for (key, color) in ["band_yellow", "band_beige", "band_brown", "band_dark_brown"].iter().zip(palette) {
+        scene.color(key, rng.vary(color, 0.08));
+    }
+    // Los gigantes de hielo tienen bandas más anchas y tormentas claras
+    let (bands, storm) = match class {
+        PlanetClass::Ice => (rng.range(3.0, 6.0), Color::new(240, 250, 255)),
+        _ => (rng.range(5.0, 10.0), rng.vary(Color::new(255, 69, 0), 0.2)),
+    };
+    scene.color("storm_color", storm);
+    scene.number("band_frequency", bands);
+    scene.number("band_warp", rng.range(0.15, 0.45));
+    scene.number("storm_longitude", rng.range(-PI, PI));
+    scene.number("storm_latitude", rng.range(-0.6, 0.6));
+}
+
+fn ringed_giant(scene: &mut SceneWriter, rng: &mut Rng, palette: [Color; 4], inner: f32, outer: f32) {
+    scene.shader("saturn");
+    writeln!(scene.text, "vertex_shader = standard").unwrap();
+    for (key, color) in ["band_color1", "band_color2", "band_color3", "band_color4"].iter().zip(palette) {
+        scene.color(key, rng.vary(color, 0.08));
+    }
+    let ring = rng.vary(palette[1], 0.1).lerp(&Color::new(200, 200, 200), 0.5);
+    scene.color("ring_color1", ring);
+    scene.color("ring_color2", ring * 0.8);
+    scene.color("ring_color3", ring.lerp(&Color::new(255, 255, 255), 0.3));
+    scene.number("ring_inner", inner);
+    scene.number("ring_outer", outer);
+    scene.number("band_scale", rng.range(3.0, 6.0));
+}
+
+// Redondea para que la escena impresa sea legible; las velocidades necesitan más decimales
+fn round(value: f32) -> f32 {
+    round_to(value, 4)
+}
+
+fn round_to(value: f32, decimals: i32) -> f32 {
+    let scale = 10.0_f32.powi(decimals);
+    (value * scale).round() / scale
+}
+
+#[derive(Default)]
+struct SceneWriter {
+    text: String,
+}
+
+impl SceneWriter {
+    fn body(&mut self, name: &str, parent: Option<&str>, orbit_radius: f32, orbit_speed: f32, size: f32) {
+        writeln!(self.text, "\n[{}]", name).unwrap();
+        if let Some(parent) = parent {
+            writeln!(self.text, "parent = {}", parent).unwrap();
+        }
+        writeln!(self.text, "orbit_radius = {}", round(orbit_radius)).unwrap();
+        writeln!(self.text, "orbit_speed = {}", round_to(orbit_speed, 7)).unwrap();
+        writeln!(self.text, "size = {}", round(size)).unwrap();
+    }
+
+    // Excentricidad e inclinación dadas; el periastro y el nodo ascendente se orientan al azar
+    fn orbit(&mut self, eccentricity: f32, inclination: f32, rng: &mut Rng) {
+        self.number("eccentricity", eccentricity);
+        self.number("inclination", inclination);
+        self.number("periapsis", rng.range(0.0, 360.0));
+        self.number("ascending_node", rng.range(0.0, 360.0));
+    }
+
+    fn shader(&mut self, name: &str) {
+        writeln!(self.text, "shader = {}", name).unwrap();
+    }
+
+    fn number(&mut self, key: &str, value: f32) {
+        writeln!(self.text, "{} = {}", key, round(value)).unwrap();
+    }
+
+    fn color(&mut self, key: &str, color: Color) {
+        writeln!(self.text, "{} = {}", key, ShaderParam::Color(color)).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Scene;
+
+    // Incluye semillas que antes daban cometas con el perihelio más allá del afelio (689, 1034, 1191)
+    #[test]
+    fn generated_scenes_parse() {
+        // 689, 1034 y 1191 generaban cometas con excentricidad fuera de rango
+        for seed in (0..40).chain([689, 1034, 1191]) {
+            let scene = Scene::parse(&generate(seed)).unwrap_or_else(|err| panic!("semilla {}: {}", seed, err));
+            for planet in &scene.planets {
+                assert!(
+                    (0.0..1.0).contains(&planet.eccentricity),
+                    "semilla {}: {} tiene excentricidad {}",
+                    seed,
+                    planet.name,
+                    planet.eccentricity,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_system() {
+        for seed in [0, 7, 689] {
+            assert_eq!(generate(seed), generate(seed));
+        }
+        assert_ne!(generate(1), generate(2));
+        assert_ne!(generate(689), generate(1034));
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use gltf::image::Format;
+use gltf::mesh::Mode;
+use nalgebra_glm::{mat4_to_mat3, Mat4, Vec2, Vec3, Vec4};
+use crate::color::Color;
+use crate::lighting::DIELECTRIC_SPECULAR;
+use crate::mesh::{Mesh, NormalMode};
+use crate::model::{Model, SurfaceMaterial};
+use crate::texture::Texture;
+use crate::vertex::Vertex;
+
+// Carga .gltf (con buffers externos o embebidos) y .glb
+pub fn load(filename: &str) -> Result<Model, String> {
+    let (document, buffers, images) = gltf::import(filename)
+        .map_err(|err| format!("No se pudo cargar {}: {}", filename, err))?;
+
+    let textures: Vec<Option<Arc<Texture>>> = images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let texture = texture(image).map(Arc::new);
+            if texture.is_none() {
+                eprintln!("{}: formato de imagen {} no soportado: {:?}", filename, index, image.format);
+            }
+            texture
+        })
+        .collect();
+    let image_of = |texture: gltf::Texture| textures.get(texture.source().index()).cloned().flatten();
+
+    let materials = document
+        .materials()
+        .map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+            let [r, g, b, _] = pbr.base_color_factor();
+            SurfaceMaterial {
+                name: material.name().unwrap_or("sin nombre").to_string(),
+                diffuse: Color::from_rgb_f32([r, g, b]),
+                specular: DIELECTRIC_SPECULAR,
+                emissive: Color::from_rgb_f32(material.emissive_factor()),
+                metallic: pbr.metallic_factor(),
+                roughness: pbr.roughness_factor(),
+                diffuse_map: pbr.base_color_texture().and_then(|info| image_of(info.texture())),
+                specular_map: None,
+                emissive_map: material.emissive_texture().and_then(|info| image_of(info.texture())),
+                metallic_roughness_map: pbr.metallic_roughness_texture().and_then(|info| image_of(info.texture())),
+                normal_map: material.normal_texture().and_then(|info| image_of(info.texture())),
+                normal_scale: material.normal_texture().map(|info| info.scale()).unwrap_or(1.0),
+            }
+        })
+        .collect();
+
+    // Se recorre la jerarquía de nodos acumulando transformaciones; la geometría queda en el espacio del modelo
+    let mut meshes = Vec::new();
+    let scene = document.default_scene().or_else(|| document.scenes().next());
+    let roots: Vec<gltf::Node> = match scene {
+        Some(scene) => scene.nodes().collect(),
+        None => document.nodes().collect(),
+    };
+    for node in roots {
+        collect_node(&node, &Mat4::identity(), &buffers, filename, &mut meshes);
+    }
+
+    Ok(Model {
+        source: filename.to_string(),
+        meshes,
+        materials,
+        normals: None,
+    })
+}
+
+fn collect_node(
+    node: &gltf::Node,
+    parent_transform: &Mat4,
+    buffers: &[gltf::buffer::Data],
+    filename: &str,
+    meshes: &mut Vec<Mesh>,
+) {
+    let transform = parent_transform * Mat4::from(node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        let normal_matrix = mat4_to_mat3(&transform).try_inverse().map(|inverse| inverse.transpose());
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                eprintln!("{}: se omite una primitiva en modo {:?}", filename, primitive.mode());
+                continue;
+            }
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let positions: Vec<Vec3> = positions
+                .map(|[x, y, z]| (transform * Vec4::new(x, y, z, 1.0)).xyz())
+                .collect();
+            let mut normals = reader.read_normals().map(|normals| normals.map(|[x, y, z]| {
+                let normal = Vec3::new(x, y, z);
+                normal_matrix.map_or(normal, |matrix| (matrix * normal).normalize())
+            }));
+            let mut tex_coords = reader.read_tex_coords(0).map(|tex_coords| tex_coords.into_f32());
+
+            let vertices = positions
+                .into_iter()
+                .map(|position| {
+                    let normal = normals.as_mut().and_then(Iterator::next).unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                    let tex_coords = tex_coords
+                        .as_mut()
+                        .and_then(Iterator::next)
+                        .map(|[u, v]| Vec2::new(u, v))
+                        .unwrap_or(Vec2::zeros());
+                    Vertex::new(position, normal, tex_coords)
+                })
+                .collect::<Vec<_>>();
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+
+            let mut generated = Mesh::new(vertices, indices).with_material(primitive.material().index());
+            if normals.is_none() {
+                generated.compute_normals(NormalMode::Smooth);
+            }
+            match reader.read_tangents() {
+                Some(tangents) => {
+                    for (vertex, [x, y, z, w]) in generated.vertices.iter_mut().zip(tangents) {
+                        let tangent = mat4_to_mat3(&transform) * Vec3::new(x, y, z);
+                        let tangent = tangent.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+                        vertex.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, w);
+                    }
+                }
+                None => generated.compute_tangents(),
+            }
+            meshes.push(generated);
+        }
+    }
+
+    for child in node.children() {
+        collect_node(&child, &transform, buffers, filename, meshes);
+    }
+}
+
+fn texture(image: &gltf::image::Data) -> Option<Texture> {
+    let channels = match image.format {
+        Format::R8 => 1,
+        Format::R8G8 => 2,
+        Format::R8G8B8 => 3,
+        Format::R8G8B8A8 => 4,
+        _ => return None,
+    };
+    let pixels = image
+        .pixels
+        .chunks_exact(channels)
+        .map(|pixel| match pixel {
+            [gray] | [gray, _] => Color::new(*gray, *gray, *gray),
+            [r, g, b, ..] => Color::new(*r, *g, *b),
+            _ => Color::black(),
+        })
+        .collect();
+    Some(Texture::from_pixels(image.width as usize, image.height as usize, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-4;
+
+    fn bounds(mesh: &Mesh) -> (Vec3, Vec3) {
+        mesh.vertices.iter().fold(
+            (Vec3::repeat(f32::MAX), Vec3::repeat(f32::MIN)),
+            |(min, max), vertex| (min.inf(&vertex.position), max.sup(&vertex.position)),
+        )
+    }
+
+    fn assert_bounds(mesh: &Mesh, min: [f32; 3], max: [f32; 3]) {
+        let (actual_min, actual_max) = bounds(mesh);
+        assert!(
+            (actual_min - Vec3::from(min)).abs().max() < TOLERANCE && (actual_max - Vec3::from(max)).abs().max() < TOLERANCE,
+            "límites {:?}..{:?}, se esperaba {:?}..{:?}",
+            actual_min,
+            actual_max,
+            min,
+            max,
+        );
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < TOLERANCE, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn station_applies_node_hierarchy() {
+        let model = load("assets/models/station.gltf").unwrap();
+
+        // núcleo (2 primitivas) y el módulo instanciado en dos nodos (2 primitivas cada uno)
+        assert_eq!(model.meshes.len(), 6);
+        let materials: Vec<Option<usize>> = model.meshes.iter().map(|mesh| mesh.material).collect();
+        assert_eq!(materials, [Some(0), Some(0), Some(1), Some(2), Some(1), Some(2)]);
+
+        // La raíz gira 90° sobre Z: (x, y, z) pasa a (-y, x, z)
+        assert_bounds(&model.meshes[1], [-1.2, -0.15, -0.15], [-0.9, 0.15, 0.15]);
+        // modulo_a: traslación en Z bajo la raíz
+        assert_bounds(&model.meshes[2], [-0.3, -0.4, 0.55], [0.3, 0.4, 1.15]);
+        assert_bounds(&model.meshes[3], [-0.12, 0.4, 0.65], [0.12, 0.44, 1.05]);
+        // modulo_b: giro de 180° sobre Y, traslación del brazo y giro de la raíz
+        assert_bounds(&model.meshes[5], [-0.12, -0.44, -1.05], [0.12, -0.4, -0.65]);
+
+        for mesh in &model.meshes {
+            for vertex in &mesh.vertices {
+                assert_close(vertex.normal.norm(), 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn station_reads_pbr_factors() {
+        let model = load("assets/models/station.gltf").unwrap();
+        let factors: Vec<(&str, f32, f32)> = model
+            .materials
+            .iter()
+            .map(|material| (material.name.as_str(), material.metallic, material.roughness))
+            .collect();
+        assert_eq!(factors, [("aluminio", 0.9, 0.35), ("panel", 0.0, 0.8), ("ventana", 0.0, 0.1)]);
+
+        assert!(model.materials[1].diffuse_map.is_some());
+        assert_eq!(model.materials[2].emissive.to_hex(), Color::from_rgb_f32([1.0, 0.85, 0.4]).to_hex());
+    }
+
+    #[test]
+    fn probe_glb_applies_scale_to_children() {
+        let model = load("assets/models/probe.glb").unwrap();
+
+        assert_eq!(model.meshes.len(), 3);
+        // La escala 1.2 de la raíz también afecta a la antena, que cuelga de ella
+        assert_bounds(&model.meshes[0], [-0.36, -0.36, -0.36], [0.36, 0.36, 0.36]);
+        assert_bounds(&model.meshes[1], [-0.036, 0.36, -0.036], [0.036, 1.2, 0.036]);
+        assert_bounds(&model.meshes[2], [-0.42, 1.2, -0.42], [0.42, 1.26, 0.42]);
+    }
+
+    #[test]
+    fn probe_glb_decodes_embedded_texture() {
+        let model = load("assets/models/probe.glb").unwrap();
+        let lamina = &model.materials[0];
+        assert_eq!(lamina.name, "lamina");
+        assert_close(lamina.metallic, 1.0);
+        assert_close(lamina.roughness, 1.0);
+
+        // Textura de 8x8: azul (metalicidad) 255 en la mitad izquierda y 40 en la derecha
+        let texture = lamina.metallic_roughness_map.as_ref().expect("la textura embebida no se decodificó");
+        let texel = |x: f32, y: f32| texture.sample(Vec2::new((x + 0.5) / 8.0, (y + 0.5) / 8.0)).to_hex();
+        assert_eq!(texel(0.0, 0.0), Color::new(0, 60, 255).to_hex());
+        assert_eq!(texel(4.0, 0.0), Color::new(0, 60, 40).to_hex());
+        assert_eq!(texel(1.0, 2.0), Color::new(0, 120, 255).to_hex());
+
+        let (metallic, roughness) = lamina.metallic_roughness_at(Vec2::new(4.5 / 8.0, 0.5 / 8.0));
+        assert_close(metallic, 40.0 / 255.0);
+        assert_close(roughness, 60.0 / 255.0);
+    }
+}
@@ -0,0 +1,105 @@
+use nalgebra_glm::distance;
+use crate::camera::Camera;
+use crate::clock::SimClock;
+use crate::framebuffer::Framebuffer;
+use crate::lod::LodMeshes;
+use crate::planet::Planet;
+use crate::profiler::{FrameProfile, Stage};
+use crate::scene::Scene;
+use crate::text::{draw_text, GLYPH_ADVANCE, LINE_HEIGHT};
+
+pub fn draw_hud(
+    output: &mut Framebuffer,
+    fps: u32,
+    profile: &FrameProfile,
+    clock: &SimClock,
+    camera: &Camera,
+    scene: &Scene,
+    selected: Option<usize>,
+) {
+    let mut lines = vec![
+        format!("FPS {}  frame {:.1} ms", fps, profile.total_ms()),
+        format!(
+            "upd {:.1} clr {:.1} vtx {:.1} sort {:.1} ras {:.1}",
+            profile.stage_ms(Stage::Update),
+            profile.stage_ms(Stage::Clear),
+            profile.stage_ms(Stage::Vertex),
+            profile.stage_ms(Stage::Sort),
+            profile.stage_ms(Stage::Raster)
+        ),
+        format!(
+            "shade {:.1} post {:.1} present {:.1} ms",
+            profile.stage_ms(Stage::Shade),
+            profile.stage_ms(Stage::Post),
+            profile.stage_ms(Stage::Present)
+        ),
+        format!(
+            "tris {}  frags {}  overdraw {:.2}",
+            profile.triangles,
+            profile.fragments,
+            profile.overdraw()
+        ),
+        format!(
+            "Tiempo {:.0}  escala x{}{}",
+            clock.elapsed,
+            clock.scale,
+            if clock.paused { "  PAUSA" } else { "" }
+        ),
+        format!("Camara ({:.1}, {:.1}, {:.1})", camera.eye.x, camera.eye.y, camera.eye.z),
+    ];
+    if let Some(ephemeris) = &scene.ephemeris {
+        lines.insert(5, format!("Fecha {}", ephemeris.date()));
+    }
+    match selected.and_then(|index| scene.planets.get(index)) {
+        Some(planet) => lines.push(format!(
+            "Seleccion: {}  distancia {:.1}",
+            planet.name,
+            distance(&camera.eye, &planet.position)
+        )),
+        None => lines.push(String::from("Seleccion: ninguna (Tab)")),
+    }
+
+    let height = lines.len() * LINE_HEIGHT + 12;
+    let y = output.height.saturating_sub(height + 10);
+    output.blend_rect(10, y, 300, height, 0x000000, 0.6);
+    for (index, line) in lines.iter().enumerate() {
+        draw_text(output, 18, y + 8 + index * LINE_HEIGHT, line, 0xE0E0E0, 1);
+    }
+}
+
+pub fn draw_body_info(output: &mut Framebuffer, planet: &Planet, camera: &Camera, meshes: &LodMeshes, body: usize) {
+    let period = if planet.orbit_speed.abs() > f32::EPSILON {
+        format!("{:.0}", std::f32::consts::TAU / planet.orbit_speed.abs())
+    } else {
+        String::from("-")
+    };
+    let mut lines = vec![
+        planet.name.clone(),
+        format!("Radio: {:.2}", planet.size * planet.model.as_ref().map_or(meshes.radius(), |model| model.radius())),
+        format!("Periodo orbital: {}", period),
+        format!("Distancia al Sol: {:.1}", planet.position.norm()),
+        format!("Distancia a la camara: {:.1}", distance(&camera.eye, &planet.position)),
+    ];
+    match (&planet.model, meshes.current_level(body)) {
+        (Some(model), _) => {
+            let triangles: usize = model.meshes.iter().map(|mesh| mesh.triangle_count()).sum();
+            let names: Vec<&str> = model.materials.iter().map(|material| material.name.as_str()).collect();
+            lines.push(format!("Modelo: {} tris", triangles));
+            lines.push(format!("Materiales: {}", names.join(", ")));
+        }
+        (None, Some(level)) => {
+            lines.push(format!("Nivel de detalle: {} ({} tris)", level, meshes.level(level).triangle_count()));
+        }
+        (None, None) => lines.push(String::from("Nivel de detalle: -")),
+    }
+
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let width = (longest * GLYPH_ADVANCE + 16).max(220);
+    let height = lines.len() * LINE_HEIGHT + 12;
+    let x = output.width.saturating_sub(width + 10);
+    output.blend_rect(x, 10, width, height, 0x000000, 0.6);
+    for (index, line) in lines.iter().enumerate() {
+        let color = if index == 0 { 0x00FFFF } else { 0xE0E0E0 };
+        draw_text(output, x + 8, 18 + index * LINE_HEIGHT, line, color, 1);
+    }
+}
@@ -0,0 +1,130 @@
+use std::f32::consts::PI;
+use nalgebra_glm::{dot, Vec3, Vec4};
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::model::shininess_from_roughness;
+use crate::Uniforms;
+
+// Reflectancia a incidencia normal de los dieléctricos (~4 %)
+pub const DIELECTRIC_SPECULAR: Color = Color::new(10, 10, 10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightingModel {
+    BlinnPhong,
+    CookTorrance,
+}
+
+// Propiedades de la superficie en el punto sombreado
+pub struct SurfacePoint {
+    pub albedo: Color,
+    // Color del reflejo de los dieléctricos; los metales reflejan con su albedo
+    pub specular: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+}
+
+impl SurfacePoint {
+    pub fn dielectric(albedo: Color, roughness: f32) -> Self {
+        SurfacePoint {
+            albedo,
+            specular: DIELECTRIC_SPECULAR,
+            metallic: 0.0,
+            roughness,
+        }
+    }
+}
+
+fn world_position(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let position = fragment.vertex_position;
+    (uniforms.model_matrix * Vec4::new(position.x, position.y, position.z, 1.0)).xyz()
+}
+
+// Dirección hacia el observador desde el fragmento, en espacio del mundo
+pub fn view_dir(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    (uniforms.eye - world_position(fragment, uniforms))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vec3::new(0.0, 0.0, 1.0))
+}
+
+// Dirección hacia el Sol (luz puntual) desde el fragmento, en espacio del mundo
+pub fn light_dir(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    (uniforms.light_position - world_position(fragment, uniforms))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(fragment.normal)
+}
+
+// Brillo de una esfera difusa vista con el ángulo de fase cuyo coseno se da, relativo a verla
+// llena (fase 0); en fase π (nueva) no refleja nada hacia el observador
+pub fn lambert_phase(cos_phase: f32) -> f32 {
+    let phase = cos_phase.clamp(-1.0, 1.0).acos();
+    (phase.sin() + (PI - phase) * phase.cos()) / PI
+}
+
+// Luz directa reflejada hacia el observador (sin término ambiente).
+// La luz tiene intensidad π para que una superficie difusa blanca dé exactamente N·L
+pub fn shade(model: LightingModel, surface: &SurfacePoint, normal: &Vec3, view: &Vec3, light: &Vec3) -> Color {
+    let n_dot_l = dot(normal, light).max(0.0);
+    if n_dot_l <= 0.0 {
+        return Color::black();
+    }
+    let n_dot_v = dot(normal, view).max(1e-4);
+    let half = (light + view).try_normalize(f32::EPSILON).unwrap_or(*normal);
+    let n_dot_h = dot(normal, &half).max(0.0);
+
+    let albedo = Vec3::from(surface.albedo.to_rgb_f32());
+    let f0 = Vec3::from(surface.specular.to_rgb_f32()).lerp(&albedo, surface.metallic.clamp(0.0, 1.0));
+    let diffuse_weight = 1.0 - surface.metallic.clamp(0.0, 1.0);
+
+    let radiance = match model {
+        LightingModel::BlinnPhong => {
+            // Exponente normalizado para que el brillo total no dependa de la rugosidad
+            let shininess = shininess_from_roughness(surface.roughness);
+            let specular = f0 * ((shininess + 8.0) / 8.0 * n_dot_h.powf(shininess));
+            (albedo * diffuse_weight + specular) * n_dot_l
+        }
+        LightingModel::CookTorrance => {
+            let fresnel = schlick(&f0, dot(view, &half).max(0.0));
+            let specular = fresnel
+                * (ggx_distribution(n_dot_h, surface.roughness) * smith_geometry(n_dot_v, n_dot_l, surface.roughness)
+                    / (4.0 * n_dot_v * n_dot_l)
+                    * PI);
+            let diffuse = (Vec3::repeat(1.0) - fresnel).component_mul(&albedo) * diffuse_weight;
+            (diffuse + specular) * n_dot_l
+        }
+    };
+
+    Color::from_rgb_f32([radiance.x, radiance.y, radiance.z])
+}
+
+// Fresnel de Schlick
+fn schlick(f0: &Vec3, cos_theta: f32) -> Vec3 {
+    f0 + (Vec3::repeat(1.0) - f0) * (1.0 - cos_theta).powi(5)
+}
+
+// Distribución de microfacetas GGX / Trowbridge-Reitz
+fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness.clamp(0.05, 1.0).powi(2);
+    let alpha2 = alpha * alpha;
+    let denominator = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * denominator * denominator)
+}
+
+// Sombreado de Smith con la aproximación de Schlick-GGX para luces directas
+fn smith_geometry(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0).powi(2) / 8.0;
+    let schlick_ggx = |cos_theta: f32| cos_theta / (cos_theta * (1.0 - k) + k);
+    schlick_ggx(n_dot_v) * schlick_ggx(n_dot_l)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lambert_phase_goes_from_full_to_new() {
+        assert!((lambert_phase(1.0) - 1.0).abs() < 1e-3);
+        assert!((lambert_phase(0.0) - 1.0 / PI).abs() < 1e-3);
+        assert!(lambert_phase(-1.0).abs() < 1e-3);
+        assert!(lambert_phase(0.5) > lambert_phase(0.4));
+    }
+}
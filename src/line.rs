@@ -0,0 +1,158 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+
+pub struct LineProjection {
+    pub view_projection: Mat4,
+    pub viewport: Mat4,
+}
+
+impl LineProjection {
+    // Proyecta un segmento del mundo a pantalla, recortándolo contra el plano cercano (z >= -w)
+    pub fn project_segment(&self, a: &Vec3, b: &Vec3) -> Option<(Vec3, Vec3)> {
+        let mut start = self.view_projection * Vec4::new(a.x, a.y, a.z, 1.0);
+        let mut end = self.view_projection * Vec4::new(b.x, b.y, b.z, 1.0);
+        let start_distance = start.z + start.w;
+        let end_distance = end.z + end.w;
+        if start_distance < 0.0 && end_distance < 0.0 {
+            return None;
+        }
+        if start_distance < 0.0 {
+            start = start.lerp(&end, start_distance / (start_distance - end_distance));
+        } else if end_distance < 0.0 {
+            end = end.lerp(&start, end_distance / (end_distance - start_distance));
+        }
+
+        let to_screen = |clip: Vec4| {
+            let screen = self.viewport * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            Vec3::new(screen.x, screen.y, screen.z)
+        };
+        Some((to_screen(start), to_screen(end)))
+    }
+
+    // Posición en pantalla (x, y, profundidad) de un punto delante del plano cercano
+    pub fn project_point(&self, point: &Vec3) -> Option<Vec3> {
+        let clip = self.view_projection * Vec4::new(point.x, point.y, point.z, 1.0);
+        if clip.z < -clip.w || clip.w <= f32::EPSILON {
+            return None;
+        }
+        let screen = self.viewport * (clip / clip.w);
+        Some(Vec3::new(screen.x, screen.y, screen.z))
+    }
+}
+
+// Dibuja una línea en pantalla (x, y, profundidad) sobre el buffer resuelto con prueba de profundidad
+pub fn draw_line(framebuffer: &mut Framebuffer, a: Vec3, b: Vec3, color: u32, alpha: f32, antialiased: bool) {
+    let Some((a, b)) = clip_to_viewport(a, b, framebuffer.width as f32, framebuffer.height as f32) else {
+        return;
+    };
+    if antialiased {
+        draw_line_wu(framebuffer, a, b, color, alpha);
+    } else {
+        draw_line_bresenham(framebuffer, a, b, color, alpha);
+    }
+}
+
+// Liang-Barsky: evita recorrer píxeles fuera de la pantalla en segmentos muy largos
+fn clip_to_viewport(a: Vec3, b: Vec3, width: f32, height: f32) -> Option<(Vec3, Vec3)> {
+    let delta = b - a;
+    let mut t_enter: f32 = 0.0;
+    let mut t_exit: f32 = 1.0;
+    let bounds = [
+        (-delta.x, a.x),
+        (delta.x, width - 1.0 - a.x),
+        (-delta.y, a.y),
+        (delta.y, height - 1.0 - a.y),
+    ];
+    for (p, q) in bounds {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t_enter = t_enter.max(q / p);
+        } else {
+            t_exit = t_exit.min(q / p);
+        }
+    }
+    if t_enter > t_exit {
+        return None;
+    }
+    Some((a + delta * t_enter, a + delta * t_exit))
+}
+
+fn draw_line_bresenham(framebuffer: &mut Framebuffer, a: Vec3, b: Vec3, color: u32, alpha: f32) {
+    let (mut x, mut y) = (a.x.floor() as i32, a.y.floor() as i32);
+    let (x_end, y_end) = (b.x.floor() as i32, b.y.floor() as i32);
+    let dx = (x_end - x).abs();
+    let dy = -(y_end - y).abs();
+    let step_x = if x < x_end { 1 } else { -1 };
+    let step_y = if y < y_end { 1 } else { -1 };
+    let steps = dx.max(-dy).max(1) as f32;
+    let mut error = dx + dy;
+    let mut step = 0.0;
+
+    loop {
+        let depth = a.z + (b.z - a.z) * (step / steps);
+        framebuffer.blend_point_depth(x as usize, y as usize, depth, color, alpha);
+        if x == x_end && y == y_end {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        step += 1.0;
+    }
+}
+
+// Xiaolin Wu: reparte la intensidad entre los dos píxeles más cercanos a la línea
+fn draw_line_wu(framebuffer: &mut Framebuffer, a: Vec3, b: Vec3, color: u32, alpha: f32) {
+    // Coordenadas relativas al centro del píxel
+    let center = Vec3::new(0.5, 0.5, 0.0);
+    let (a, b) = (a - center, b - center);
+    let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+    let (mut a, mut b) = if steep {
+        (Vec3::new(a.y, a.x, a.z), Vec3::new(b.y, b.x, b.z))
+    } else {
+        (a, b)
+    };
+    if a.x > b.x {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    let dx = b.x - a.x;
+    let gradient = if dx.abs() < f32::EPSILON { 1.0 } else { (b.y - a.y) / dx };
+    let depth_gradient = if dx.abs() < f32::EPSILON { 0.0 } else { (b.z - a.z) / dx };
+    let mut plot = |major: i32, minor: i32, depth: f32, coverage: f32| {
+        let (x, y) = if steep { (minor, major) } else { (major, minor) };
+        if x >= 0 && y >= 0 && coverage > 0.0 {
+            framebuffer.blend_point_depth(x as usize, y as usize, depth, color, alpha * coverage);
+        }
+    };
+
+    let x_start = a.x.round() as i32;
+    let x_end = b.x.round() as i32;
+    for x in x_start..=x_end {
+        // Los extremos solo cubren la parte del píxel que abarca el segmento
+        let span = if x_start == x_end {
+            dx.max(0.0)
+        } else if x == x_start {
+            0.5 - (a.x - x as f32)
+        } else if x == x_end {
+            0.5 + (b.x - x as f32)
+        } else {
+            1.0
+        };
+        let offset = x as f32 - a.x;
+        let y = a.y + gradient * offset;
+        let depth = a.z + depth_gradient * offset;
+        let fraction = y - y.floor();
+        plot(x, y.floor() as i32, depth, (1.0 - fraction) * span);
+        plot(x, y.floor() as i32 + 1, depth, fraction * span);
+    }
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use crate::mesh::Mesh;
+
+// Radio proyectado (en píxeles) a partir del cual se usa el nivel 1; cada nivel siguiente lo multiplica por LOD_STEP
+const LOD_BASE_RADIUS: f32 = 4.0;
+const LOD_STEP: f32 = 2.7;
+// Margen relativo alrededor de cada umbral para que el nivel no oscile en el límite
+const HYSTERESIS: f32 = 0.15;
+
+// Niveles de detalle de la malla de los cuerpos, del más simple al más detallado
+pub struct LodMeshes {
+    levels: Vec<Mesh>,
+    radius: f32,
+    current: RefCell<Vec<usize>>,
+}
+
+impl LodMeshes {
+    pub fn new(levels: Vec<Mesh>, radius: f32) -> Self {
+        assert!(!levels.is_empty(), "se necesita al menos un nivel de detalle");
+        LodMeshes {
+            levels,
+            radius,
+            current: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn level(&self, level: usize) -> &Mesh {
+        &self.levels[level]
+    }
+
+    pub fn current_level(&self, body: usize) -> Option<usize> {
+        self.current.borrow().get(body).copied()
+    }
+
+    // Elige el nivel del cuerpo según su radio en pantalla, partiendo del nivel del frame anterior
+    pub fn select(&self, body: usize, projected_radius: f32) -> usize {
+        let mut current = self.current.borrow_mut();
+        if current.len() <= body {
+            current.resize(body + 1, 0);
+        }
+
+        let max_level = self.levels.len() - 1;
+        let mut level = current[body].min(max_level);
+        while level < max_level && projected_radius >= threshold(level + 1) * (1.0 + HYSTERESIS) {
+            level += 1;
+        }
+        while level > 0 && projected_radius < threshold(level) * (1.0 - HYSTERESIS) {
+            level -= 1;
+        }
+
+        current[body] = level;
+        level
+    }
+}
+
+fn threshold(level: usize) -> f32 {
+    LOD_BASE_RADIUS * LOD_STEP.powi(level as i32 - 1)
+}
+
+// Radio aproximado en píxeles de una esfera vista con una proyección en perspectiva
+pub fn projected_radius(world_radius: f32, distance: f32, fov_y: f32, viewport_height: f32) -> f32 {
+    if distance <= world_radius {
+        return f32::INFINITY;
+    }
+    world_radius / (distance * (fov_y / 2.0).tan()) * viewport_height / 2.0
+}
@@ -1,5 +1,5 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, ScaleMode, Window, WindowOptions};
 use std::time::Duration;
 
 mod framebuffer;
@@ -11,16 +11,55 @@ mod fragment;
 mod shaders;
 mod camera;
 mod planet; 
+mod config;
+mod record;
+mod fxaa;
+mod material;
+mod scene;
+mod text;
+mod watcher;
+mod param_panel;
+mod clock;
+mod hud;
+mod profiler;
+mod line;
+mod orbit;
+mod sphere;
+mod lod;
+mod mesh;
+mod model;
+mod texture;
+mod gltf_model;
+mod bump;
+mod lighting;
+mod noise;
+mod generator;
+mod asteroids;
+mod particles;
+mod ephemeris;
 use triangle::rasterize_parallel;
 use std::time::Instant;
-use crate::color::Color;
 use framebuffer::Framebuffer;
 use vertex::Vertex;
-use obj::Obj;
 use camera::Camera;
-use shaders::{earth_shader, jupiter_shader, mars_shader,sun_shader, saturn_shader};
-use planet::Planet; 
+use config::Config;
+use material::Material;
+use scene::Scene;
+use watcher::FileWatcher;
+use param_panel::{PanelAction, ParamPanel};
+use clock::SimClock;
+use profiler::{Profiler, Stage};
+use line::LineProjection;
+use orbit::OrbitRenderer;
+use sphere::SphereKind;
+use lod::LodMeshes;
+use mesh::Mesh;
+use model::{Model, SurfaceMaterial, DEFAULT_SURFACE};
+use lighting::LightingModel;
+use asteroids::AsteroidBelt;
+use fragment::Fragment;
 use fastnoise_lite::{FastNoiseLite, NoiseType};
+use rayon::prelude::*;
 
 pub struct Uniforms<'a> {
     model_matrix: Mat4,
@@ -29,10 +68,29 @@ pub struct Uniforms<'a> {
     viewport_matrix: Mat4,
     time: u32,
     noise: &'a FastNoiseLite,
+    surface: &'a SurfaceMaterial,
+    eye: Vec3,
+    light_position: Vec3,
+    lighting: LightingModel,
 }
 
-fn create_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
+pub struct RenderContext<'a> {
+    pub meshes: &'a LodMeshes,
+    pub noise: &'a FastNoiseLite,
+    pub fxaa: bool,
+    pub lighting: LightingModel,
+    pub profiler: &'a Profiler,
+}
+
+const FOV_Y: f32 = 45.0 * std::f32::consts::PI / 180.0;
+const NEAR_PLANE: f32 = 0.1;
+// Radio de las esferas generadas; coincide con el de assets/models/sphere.obj
+const SPHERE_RADIUS: f32 = 0.5;
+// Radio proyectado (en píxeles) por debajo del cual un asteroide se dibuja como un punto
+const IMPOSTOR_RADIUS: f32 = 1.0;
+
+fn create_noise(seed: i32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
     noise.set_noise_type(Some(NoiseType::OpenSimplex2));
     noise
 }
@@ -43,9 +101,8 @@ fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
 }
 
 fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * std::f32::consts::PI / 180.0;
     let aspect_ratio = window_width / window_height;
-    perspective(fov, aspect_ratio, 0.1, 1000.0)
+    perspective(FOV_Y, aspect_ratio, NEAR_PLANE, 1000.0)
 }
 
 fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
@@ -60,53 +117,265 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
 fn render(
     framebuffer: &mut Framebuffer,
     uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_index: usize,
+    mesh: &Mesh,
+    material: &Material,
+    profiler: &Profiler,
 ) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = shaders::vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-    triangles.sort_by(|a, b| {
-        let z_a = (a[0].transformed_position.z + a[1].transformed_position.z + a[2].transformed_position.z) / 3.0;
-        let z_b = (b[0].transformed_position.z + b[1].transformed_position.z + b[2].transformed_position.z) / 3.0;
-        z_b.partial_cmp(&z_a).unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    let fragments = rasterize_parallel(&triangles);
-
+    let samples = framebuffer.samples;
+    let vertex_timer = profiler.scope(Stage::Vertex);
+    let transformed_vertices: Vec<Vertex> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| material.transform(vertex, uniforms))
+        .collect();
+    let mut triangles: Vec<[u32; 3]> = mesh.triangles().collect();
+    drop(vertex_timer);
+
+    let sort_timer = profiler.scope(Stage::Sort);
+    let depth = |triangle: &[u32; 3]| {
+        triangle.iter().map(|&index| transformed_vertices[index as usize].transformed_position.z).sum::<f32>() / 3.0
+    };
+    triangles.sort_by(|a, b| depth(b).partial_cmp(&depth(a)).unwrap_or(std::cmp::Ordering::Equal));
+    drop(sort_timer);
+
+    let raster_timer = profiler.scope(Stage::Raster);
+    let fragments = rasterize_parallel(&transformed_vertices, &triangles, (framebuffer.width, framebuffer.height), samples);
+    drop(raster_timer);
+    profiler.count(triangles.len(), fragments.len());
+
+    let _shade_timer = profiler.scope(Stage::Shade);
     for fragment in fragments {
         let x = fragment.position.x as usize;
         let y = fragment.position.y as usize;
 
         if x < framebuffer.width && y < framebuffer.height {
-            let color = match shader_index {
-                0 => sun_shader( uniforms),
-                1 => earth_shader(&fragment, uniforms),
-                2 => mars_shader(&fragment, uniforms),
-                3 => jupiter_shader(&fragment, uniforms),
-                4 => saturn_shader(&fragment, uniforms),
-                _ => Color::black(),
-            };
+            let color = material.shade(&fragment, uniforms);
 
             framebuffer.set_current_color(color.to_hex());
-            framebuffer.point_with_emission(x, y, fragment.depth, 0);
+            framebuffer.point_samples(x, y, fragment.coverage, &fragment.sample_depths[..samples], 0);
         }
     }
 }
 
+// Instanciado: todas las instancias comparten la malla y el material y solo cambia la matriz de modelo.
+// Cada etapa se reparte entre hilos por instancia y el z-buffer resuelve el orden entre ellas
+// No recorta contra el plano cercano: las instancias deben estar enteras delante de él
+fn render_instanced(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    mesh: &Mesh,
+    material: &Material,
+    model_matrices: &[Mat4],
+    profiler: &Profiler,
+) {
+    let samples = framebuffer.samples;
+    let viewport = (framebuffer.width, framebuffer.height);
+    let instances: Vec<Uniforms> = model_matrices
+        .iter()
+        .map(|model_matrix| Uniforms { model_matrix: *model_matrix, ..*uniforms })
+        .collect();
+
+    let vertex_timer = profiler.scope(Stage::Vertex);
+    let transformed: Vec<Vec<Vertex>> = instances
+        .par_iter()
+        .map(|instance| mesh.vertices.iter().map(|vertex| material.transform(vertex, instance)).collect())
+        .collect();
+    drop(vertex_timer);
+
+    let raster_timer = profiler.scope(Stage::Raster);
+    let fragments: Vec<(usize, Fragment)> = transformed
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(instance, vertices)| {
+            mesh.triangles()
+                .flat_map(|[a, b, c]| {
+                    triangle::triangle(&vertices[a as usize], &vertices[b as usize], &vertices[c as usize], viewport, samples)
+                })
+                .map(move |fragment| (instance, fragment))
+        })
+        .collect();
+    drop(raster_timer);
+    profiler.count(mesh.triangle_count() * instances.len(), fragments.len());
+
+    let _shade_timer = profiler.scope(Stage::Shade);
+    let colors: Vec<u32> = fragments
+        .par_iter()
+        .map(|(instance, fragment)| material.shade(fragment, &instances[*instance]).to_hex())
+        .collect();
+    for ((_, fragment), color) in fragments.iter().zip(colors) {
+        framebuffer.set_current_color(color);
+        framebuffer.point_samples(
+            fragment.position.x as usize,
+            fragment.position.y as usize,
+            fragment.coverage,
+            &fragment.sample_depths[..samples],
+            0,
+        );
+    }
+}
+
+// Los asteroides que ocupan al menos un píxel pasan por el camino instanciado; los demás se dibujan
+// como puntos con el color de su cara iluminada, atenuado según la fase con que se ven
+fn render_belt(framebuffer: &mut Framebuffer, uniforms: &Uniforms, belt: &AsteroidBelt, profiler: &Profiler) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let mut instances = Vec::new();
+    let mut points = Vec::new();
+
+    let vertex_timer = profiler.scope(Stage::Vertex);
+    for asteroid in &belt.asteroids {
+        let position = asteroid.position;
+        let view = uniforms.view_matrix * Vec4::new(position.x, position.y, position.z, 1.0);
+        // La esfera que contiene la roca queda detrás del plano cercano o lo cruza: ninguno de
+        // sus vértices debe llegar al rasterizador con w cerca de 0
+        if -view.z - asteroid.bounding_radius() <= NEAR_PLANE {
+            continue;
+        }
+        let clip = uniforms.projection_matrix * view;
+        let screen = uniforms.viewport_matrix * (clip / clip.w);
+        let radius = lod::projected_radius(
+            asteroid.bounding_radius(),
+            nalgebra_glm::distance(&uniforms.eye, &position),
+            FOV_Y,
+            height,
+        );
+        if screen.x + radius < 0.0 || screen.y + radius < 0.0 || screen.x - radius >= width || screen.y - radius >= height {
+            continue;
+        }
+        if radius >= IMPOSTOR_RADIUS {
+            instances.push(asteroid.get_model_matrix());
+        } else if screen.x >= 0.0 && screen.y >= 0.0 {
+            points.push((screen, radius, asteroid));
+        }
+    }
+    drop(vertex_timer);
+
+    render_instanced(framebuffer, uniforms, &belt.mesh, &belt.material, &instances, profiler);
+
+    let _shade_timer = profiler.scope(Stage::Shade);
+    let samples = framebuffer.samples;
+    // Cada punto sombrea, en el marco de su roca, el punto que mira al Sol
+    let colors: Vec<_> = points
+        .par_iter()
+        .map(|(_, _, asteroid)| {
+            let to_sun = (uniforms.light_position - asteroid.position)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(Vec3::y());
+            let to_eye = (uniforms.eye - asteroid.position).try_normalize(f32::EPSILON).unwrap_or(to_sun);
+            let instance = Uniforms { model_matrix: asteroid.get_model_matrix(), ..*uniforms };
+            let fragment = Fragment::new(0.0, 0.0, 0.0, to_sun, asteroid.facing_point(&to_sun));
+            belt.material.shade(&fragment, &instance) * lighting::lambert_phase(to_sun.dot(&to_eye))
+        })
+        .collect();
+    for ((screen, radius, asteroid), color) in points.iter().zip(colors) {
+        // Cubre tantas muestras como el área del disco proyectado; lo que falta se compensa con el brillo
+        let area = std::f32::consts::PI * radius * radius * samples as f32;
+        let covered = (area.ceil() as usize).clamp(1, samples);
+        let intensity = (area / covered as f32).min(1.0) * asteroid.brightness;
+        framebuffer.set_current_color((color * intensity).to_hex());
+        framebuffer.point_samples(
+            screen.x as usize,
+            screen.y as usize,
+            ((1u32 << covered) - 1) as u8,
+            &[screen.z; 4][..samples],
+            0,
+        );
+    }
+}
+
+fn render_scene(
+    framebuffer: &mut Framebuffer,
+    camera: &Camera,
+    scene: &Scene,
+    time: u32,
+    selected: Option<usize>,
+    orbits: &OrbitRenderer,
+    context: &RenderContext,
+) {
+    let clear_timer = context.profiler.scope(Stage::Clear);
+    framebuffer.clear();
+    drop(clear_timer);
+
+    let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
+    let projection_matrix = create_perspective_matrix(framebuffer.width as f32, framebuffer.height as f32);
+    let viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+
+    let light_position = scene.light_position();
+    let planets = &scene.planets;
+    for (index, planet) in planets.iter().enumerate() {
+        framebuffer.set_current_object(index as u32);
+        let mut uniforms = Uniforms {
+            model_matrix: planet.get_model_matrix(),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            noise: context.noise,
+            surface: &DEFAULT_SURFACE,
+            eye: camera.eye,
+            light_position,
+            lighting: context.lighting,
+        };
+
+        // Los modelos se dibujan por submalla, cada una con su material de superficie
+        if let Some(model) = &planet.model {
+            for mesh in &model.meshes {
+                uniforms.surface = model.material(mesh);
+                render(framebuffer, &uniforms, mesh, &planet.material, context.profiler);
+            }
+            continue;
+        }
+
+        let world_radius = planet.size * context.meshes.radius();
+        let screen_radius = lod::projected_radius(
+            world_radius,
+            nalgebra_glm::distance(&camera.eye, &planet.position),
+            FOV_Y,
+            framebuffer.height as f32,
+        );
+        let mesh = context.meshes.level(context.meshes.select(index, screen_radius));
+
+        render(framebuffer, &uniforms, mesh, &planet.material, context.profiler);
+        if let Some(rings) = &planet.rings {
+            render(framebuffer, &uniforms, &rings.mesh, &planet.material, context.profiler);
+        }
+    }
+
+    // Los asteroides no se pueden seleccionar
+    framebuffer.set_current_object(framebuffer::NO_OBJECT);
+    for belt in &scene.belts {
+        let uniforms = Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            noise: context.noise,
+            surface: &DEFAULT_SURFACE,
+            eye: camera.eye,
+            light_position,
+            lighting: context.lighting,
+        };
+        render_belt(framebuffer, &uniforms, belt, context.profiler);
+    }
+
+    let _post_timer = context.profiler.scope(Stage::Post);
+    context.profiler.set_covered_pixels(framebuffer.covered_pixels());
+    framebuffer.resolve();
+    if context.fxaa {
+        fxaa::apply(framebuffer);
+    }
+    let projection = LineProjection {
+        view_projection: projection_matrix * view_matrix,
+        viewport: viewport_matrix,
+    };
+    particles::draw(framebuffer, planets, &projection, &camera.eye, FOV_Y);
+    let antialiased = framebuffer.samples > 1 || context.fxaa;
+    orbits.draw(framebuffer, planets, selected, &projection, antialiased);
+    if let Some(selected) = selected {
+        framebuffer.outline_object(selected as u32, 0x00FFFF);
+    }
+}
+
 fn handle_input(window: &Window, camera: &mut Camera) {
     if window.is_key_down(Key::W) {
         camera.move_forward(5.0); 
@@ -140,87 +409,252 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     }
 }
 
+fn handle_time_input(window: &Window, clock: &mut SimClock) {
+    if window.is_key_pressed(Key::Period, KeyRepeat::No) {
+        clock.speed_up();
+    }
+    if window.is_key_pressed(Key::Comma, KeyRepeat::No) {
+        clock.slow_down();
+    }
+    if window.is_key_pressed(Key::P, KeyRepeat::No) {
+        clock.toggle_pause();
+    }
+}
+
+fn handle_selection_input(window: &Window, selected: &mut Option<usize>, body_count: usize) {
+    if window.is_key_pressed(Key::Tab, KeyRepeat::No) && body_count > 0 {
+        *selected = match *selected {
+            Some(index) if index + 1 < body_count => Some(index + 1),
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+    if selected.is_some_and(|index| index >= body_count) {
+        *selected = None;
+    }
+}
+
+fn create_sphere_meshes(kind: SphereKind, levels: u32) -> LodMeshes {
+    match kind {
+        SphereKind::Icosphere => LodMeshes::new(
+            (0..levels).map(|level| sphere::icosphere(level, SPHERE_RADIUS)).collect(),
+            SPHERE_RADIUS,
+        ),
+        SphereKind::UvSphere => LodMeshes::new(
+            (0..levels).map(|level| sphere::uv_sphere(6 << level, 3 << level, SPHERE_RADIUS)).collect(),
+            SPHERE_RADIUS,
+        ),
+        SphereKind::Obj => {
+            let sphere_model = Model::load("assets/models/sphere.obj").expect("No se pudo cargar sphere.obj");
+            LodMeshes::new(vec![sphere_model.into_mesh()], SPHERE_RADIUS)
+        }
+    }
+}
+
+// Selecciona el cuerpo bajo el cursor usando el buffer de objetos del último frame
+fn pick_body(window: &Window, framebuffer: &Framebuffer, window_size: (usize, usize)) -> Option<usize> {
+    let (mouse_x, mouse_y) = window.get_mouse_pos(MouseMode::Discard)?;
+    let x = (mouse_x * framebuffer.width as f32 / window_size.0 as f32) as usize;
+    let y = (mouse_y * framebuffer.height as f32 / window_size.1 as f32) as usize;
+    framebuffer.object_at(x, y).map(|object| object as usize)
+}
 
 fn main() {
-    let window_width = 800;
-    let window_height = 800;
-    let framebuffer_width = 800;
-    let framebuffer_height = 800;
+    let config = Config::from_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
 
     let frame_delay = Duration::from_millis(16);
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    let mut camera = Camera::new(
+        Vec3::new(0.0, 0.0, 500.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+
+    let meshes = create_sphere_meshes(config.sphere, config.lod_levels);
+
+    let generated = config.seed.map(generator::generate);
+    let loaded = match &generated {
+        Some(text) => Scene::parse(text).map_err(|err| format!("Sistema generado: {}", err)),
+        None => Scene::load(&config.scene),
+    };
+    let mut scene = loaded.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+    if let Some(julian_day) = config.date {
+        let missing = ephemeris::missing_planets(&scene.planets);
+        if !missing.is_empty() {
+            eprintln!("La escena no tiene {}: quedan fuera de las efemérides", missing.join(", "));
+        }
+        let ephemeris = ephemeris::Ephemeris::new(julian_day);
+        ephemeris.apply(&mut scene.planets);
+        scene.ephemeris = Some(ephemeris);
+    }
+    if config.print_scene {
+        match &generated {
+            Some(text) => print!("{}", text),
+            None => print!("{}", scene),
+        }
+        return;
+    }
+    // Un sistema generado se guarda en su propio archivo para no pisar la escena por defecto
+    let scene_path = match config.seed {
+        Some(seed) => format!("system_{}.scene", seed),
+        None => config.scene.clone(),
+    };
+
+    let noise = create_noise(config.seed.map_or(1337, generator::noise_seed));
+    let profiler = Profiler::new(config.profile_csv.as_deref()).unwrap_or_else(|err| {
+        eprintln!("No se pudo crear el archivo de perfilado: {}", err);
+        std::process::exit(1);
+    });
+    let context = RenderContext {
+        meshes: &meshes,
+        noise: &noise,
+        fxaa: config.fxaa,
+        lighting: config.lighting,
+        profiler: &profiler,
+    };
+
+    if let Some(settings) = &config.record {
+        match record::record(settings, &config, &camera, &mut scene, &context) {
+            Ok(()) => {}
+            // El codificador externo terminó y cerró la tubería: se deja de grabar sin error
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {
+                eprintln!("\nLa salida se cerró; grabación detenida");
+            }
+            Err(err) => {
+                eprintln!("\nNo se pudo grabar la secuencia: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let (mut window_width, mut window_height) = (config.width, config.height);
+    let (framebuffer_width, framebuffer_height) = config.framebuffer_size(window_width, window_height);
+    let mut framebuffer = Framebuffer::with_samples(framebuffer_width, framebuffer_height, config.msaa);
+    let mut output = Framebuffer::new(window_width, window_height);
     let mut window = Window::new(
         "Sistema Solar con Shaders",
         window_width,
         window_height,
-        WindowOptions::default(),
+        WindowOptions {
+            resize: config.resizable,
+            scale: config.window_scale,
+            scale_mode: ScaleMode::Stretch,
+            ..WindowOptions::default()
+        },
     )
     .unwrap();
 
     framebuffer.set_background_color(0x000000);
 
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 500.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-    );
-
-    let sphere_model = Obj::load("assets/models/sphere.obj").expect("No se pudo cargar sphere.obj");
-    let vertex_arrays = sphere_model.get_vertex_array();
-
-    let mut planets = vec![
-        Planet::new(0.0, 0.0, 1.5, 0),   
-        Planet::new(25.0, 0.001, 0.5, 1), 
-        Planet::new(45.0, 0.0008, 0.7, 2), 
-        Planet::new(65.0, 0.0006, 0.9, 3), 
-        Planet::new(90.0, 0.0004, 1.2, 4), 
-    ];
-
-    let noise = create_noise();
-    let  time = 0;
+    let mut clock = SimClock::new();
+    let mut fps = 0;
+    let mut hud_visible = true;
+    let mut selected = None;
+    let mut mouse_was_down = false;
     let mut frame_count = 0; 
     let mut last_time = Instant::now(); 
+    let mut scene_watcher = FileWatcher::new(&scene_path);
+    let mut param_panel = ParamPanel::new();
+    let mut orbits = OrbitRenderer::new();
 
     while window.is_open() {
+        frame_count += 1;
         if last_time.elapsed().as_secs_f32() >= 1.0 {
-            let fps = frame_count;
-            window.set_title(&format!("Sistema Solar"));
+            fps = frame_count;
             frame_count = 0;
             last_time = Instant::now();
         }
-        
-
-        handle_input(&window, &mut camera);
 
+        if config.resizable {
+            let (width, height) = window.get_size();
+            if (width, height) != (window_width, window_height) && width > 0 && height > 0 {
+                window_width = width;
+                window_height = height;
+                let (framebuffer_width, framebuffer_height) = config.framebuffer_size(width, height);
+                framebuffer = Framebuffer::with_samples(framebuffer_width, framebuffer_height, config.msaa);
+                framebuffer.set_background_color(0x000000);
+                output = Framebuffer::new(width, height);
+            }
+        }
 
-        framebuffer.clear();
-
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-
-        for planet in planets.iter_mut() {
-            planet.update_position(1.0);
+        let update_timer = profiler.scope(Stage::Update);
+        if scene_watcher.poll() {
+            match Scene::load(&scene_path) {
+                Ok(reloaded) => {
+                    scene.apply_reload(reloaded);
+                    param_panel.message = Some(String::from("Escena recargada"));
+                }
+                Err(err) => param_panel.message = Some(err),
+            }
+        }
 
-            let model_matrix = planet.get_model_matrix();
-            let uniforms = Uniforms {
-                model_matrix,
-                view_matrix,
-                projection_matrix,
-                viewport_matrix,
-                time,
-                noise: &noise,
-            };
+        handle_input(&window, &mut camera);
+        handle_time_input(&window, &mut clock);
+        let mouse_down = window.get_mouse_down(MouseButton::Left);
+        if mouse_down && !mouse_was_down {
+            selected = pick_body(&window, &framebuffer, (window_width, window_height));
+        }
+        mouse_was_down = mouse_down;
+        handle_selection_input(&window, &mut selected, scene.planets.len());
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            hud_visible = !hud_visible;
+        }
+        if window.is_key_pressed(Key::O, KeyRepeat::No) {
+            orbits.show_orbits = !orbits.show_orbits;
+        }
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            orbits.show_trails = !orbits.show_trails;
+        }
+        if let PanelAction::Save = param_panel.handle_input(&window, &mut scene, selected) {
+            // Con --date las órbitas son las de la fecha en curso y no las del archivo: no se guardan
+            if scene.ephemeris.is_some() {
+                param_panel.message = Some(String::from("Con --date no se guarda la escena"));
+            } else {
+                param_panel.message = Some(match scene.save(&scene_path) {
+                    Ok(()) => format!("Guardado en {}", scene_path),
+                    Err(err) => err,
+                });
+                scene_watcher.mark_current();
+            }
+        }
 
-            render(&mut framebuffer, &uniforms, &vertex_arrays, planet.shader_index);
+        let delta = clock.tick(1.0);
+        scene.update(delta);
+        orbits.record(&scene.planets);
+        drop(update_timer);
+
+        render_scene(&mut framebuffer, &camera, &scene, clock.shader_time(), selected, &orbits, &context);
+        let post_timer = profiler.scope(Stage::Post);
+        framebuffer.resolve_into(&mut output.buffer, window_width, window_height);
+        param_panel.draw(&mut output, &scene, selected);
+        if hud_visible {
+            hud::draw_hud(&mut output, fps, &profiler.average(), &clock, &camera, &scene, selected);
         }
+        if let Some((index, planet)) = selected.and_then(|index| Some((index, scene.planets.get(index)?))) {
+            hud::draw_body_info(&mut output, planet, &camera, &meshes, index);
+        }
+        drop(post_timer);
 
+        let present_timer = profiler.scope(Stage::Present);
         window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+            .update_with_buffer(&output.buffer, window_width, window_height)
             .unwrap();
+        drop(present_timer);
+        if let Err(err) = profiler.end_frame() {
+            eprintln!("No se pudo escribir el perfilado: {}", err);
+        }
 
         std::thread::sleep(frame_delay);
     }
-}
\ No newline at end of file
+
+    if let Err(err) = profiler.flush() {
+        eprintln!("No se pudo escribir el perfilado: {}", err);
+    }
+}
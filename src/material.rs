@@ -0,0 +1,61 @@
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::shaders::{fragment_shader_by_name, vertex_shader_by_name, FragmentShader, ShaderParam, VertexShader};
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+pub struct Material {
+    pub vertex_shader: Box<dyn VertexShader>,
+    pub fragment_shader: Box<dyn FragmentShader>,
+}
+
+impl Material {
+    pub fn new(fragment_shader: &str) -> Result<Self, String> {
+        Ok(Material {
+            vertex_shader: vertex_shader_by_name("wobble").unwrap(),
+            fragment_shader: fragment_shader_by_name(fragment_shader)
+                .ok_or(format!("Shader desconocido: {}", fragment_shader))?,
+        })
+    }
+
+    pub fn set_vertex_shader(&mut self, name: &str) -> Result<(), String> {
+        self.vertex_shader = vertex_shader_by_name(name)
+            .ok_or(format!("Vertex shader desconocido: {}", name))?;
+        Ok(())
+    }
+
+    // Parámetros de ambos shaders: primero los del fragment shader y luego los del vertex shader
+    pub fn params(&self) -> Vec<(&'static str, ShaderParam)> {
+        let mut params = self.fragment_shader.params();
+        params.extend(self.vertex_shader.params());
+        params
+    }
+
+    pub fn set_param(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if self.has_vertex_param(name) {
+            self.vertex_shader.set_param_str(name, value)
+        } else {
+            self.fragment_shader.set_param_str(name, value)
+        }
+    }
+
+    pub fn set_param_value(&mut self, name: &str, value: ShaderParam) -> Result<(), String> {
+        if self.has_vertex_param(name) {
+            self.vertex_shader.set_param(name, value)
+        } else {
+            self.fragment_shader.set_param(name, value)
+        }
+    }
+
+    fn has_vertex_param(&self, name: &str) -> bool {
+        self.vertex_shader.params().iter().any(|(param, _)| *param == name)
+    }
+
+    pub fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        self.vertex_shader.transform(vertex, uniforms)
+    }
+
+    pub fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.fragment_shader.shade(fragment, uniforms)
+    }
+}
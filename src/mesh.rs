@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use nalgebra_glm::{angle, cross, dot, Vec3, Vec4};
+use crate::vertex::Vertex;
+
+// Distancia por debajo de la cual dos vértices se consideran la misma posición
+const WELD_TOLERANCE: f32 = 1e-5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+    // Promedio ponderado por el ángulo de cada triángulo en el vértice
+    Smooth,
+    // Una normal por triángulo; los vértices se duplican
+    Flat,
+}
+
+// Malla indexada: cada vértice único se transforma una sola vez por frame
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        debug_assert!(indices.len().is_multiple_of(3), "los índices deben describir triángulos completos");
+        debug_assert!(indices.iter().all(|&index| (index as usize) < vertices.len()));
+        Mesh {
+            vertices,
+            indices,
+            material: None,
+        }
+    }
+
+    // Índice del material dentro del modelo al que pertenece la malla
+    pub fn with_material(mut self, material: Option<usize>) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]])
+    }
+
+    // Agrega otra malla desplazando sus índices
+    pub fn append(&mut self, other: Mesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices.extend(other.indices.into_iter().map(|index| index + offset));
+    }
+
+    pub fn compute_normals(&mut self, mode: NormalMode) {
+        match mode {
+            NormalMode::Smooth => self.compute_smooth_normals(),
+            NormalMode::Flat => self.compute_flat_normals(),
+        }
+    }
+
+    fn compute_smooth_normals(&mut self) {
+        // Los vértices repetidos en costuras de textura comparten normal si coinciden en posición
+        let mut welded: HashMap<[i64; 3], usize> = HashMap::new();
+        let groups: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let key = vertex.position.map(|coordinate| (coordinate / WELD_TOLERANCE).round() as i64);
+                let next = welded.len();
+                *welded.entry([key.x, key.y, key.z]).or_insert(next)
+            })
+            .collect();
+
+        let mut normals = vec![Vec3::zeros(); welded.len()];
+        for [a, b, c] in self.triangles() {
+            let [pa, pb, pc] = [a, b, c].map(|index| self.vertices[index as usize].position);
+            let Some(face_normal) = cross(&(pb - pa), &(pc - pa)).try_normalize(f32::EPSILON) else {
+                continue;
+            };
+            for (index, from, to) in [(a, pb - pa, pc - pa), (b, pc - pb, pa - pb), (c, pa - pc, pb - pc)] {
+                normals[groups[index as usize]] += face_normal * angle(&from, &to);
+            }
+        }
+
+        for (vertex, group) in self.vertices.iter_mut().zip(&groups) {
+            vertex.normal = normals[*group].try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+        }
+    }
+
+    fn compute_flat_normals(&mut self) {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+        for [a, b, c] in self.triangles() {
+            let corners = [a, b, c].map(|index| self.vertices[index as usize].clone());
+            let normal = cross(
+                &(corners[1].position - corners[0].position),
+                &(corners[2].position - corners[0].position),
+            )
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+            for mut vertex in corners {
+                vertex.normal = normal;
+                vertices.push(vertex);
+            }
+        }
+        self.indices = (0..vertices.len() as u32).collect();
+        self.vertices = vertices;
+    }
+
+    // Tangentes por vértice a partir de las coordenadas de textura, ortogonalizadas respecto a la normal
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zeros(); self.vertices.len()];
+        for [a, b, c] in self.triangles() {
+            let [va, vb, vc] = [a, b, c].map(|index| &self.vertices[index as usize]);
+            let edge1 = vb.position - va.position;
+            let edge2 = vc.position - va.position;
+            let duv1 = vb.tex_coords - va.tex_coords;
+            let duv2 = vc.tex_coords - va.tex_coords;
+            let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+            if determinant.abs() < f32::EPSILON {
+                continue;
+            }
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / determinant;
+            for index in [a, b, c] {
+                tangents[index as usize] += tangent;
+                bitangents[index as usize] += bitangent;
+            }
+        }
+
+        for ((vertex, tangent), bitangent) in self.vertices.iter_mut().zip(&tangents).zip(&bitangents) {
+            let normal = vertex.normal;
+            let tangent = (tangent - normal * dot(&normal, tangent))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| perpendicular(&normal));
+            let handedness = if dot(&cross(&normal, &tangent), bitangent) < 0.0 { -1.0 } else { 1.0 };
+            vertex.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, handedness);
+        }
+    }
+}
+
+// Cualquier vector unitario perpendicular a la normal, para vértices sin coordenadas de textura útiles
+fn perpendicular(normal: &Vec3) -> Vec3 {
+    let axis = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    cross(&axis, normal).try_normalize(f32::EPSILON).unwrap_or(axis)
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+use std::sync::Arc;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::bump;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::mesh::{Mesh, NormalMode};
+use crate::gltf_model;
+use crate::obj;
+use crate::texture::Texture;
+
+// Propiedades de superficie de una submalla. Sigue el modelo metálico-rugoso de glTF;
+// los materiales MTL se convierten al cargarlos (Ns pasa a rugosidad)
+pub struct SurfaceMaterial {
+    pub name: String,
+    pub diffuse: Color,
+    pub specular: Color,
+    pub emissive: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub diffuse_map: Option<Arc<Texture>>,
+    pub specular_map: Option<Arc<Texture>>,
+    pub emissive_map: Option<Arc<Texture>>,
+    // Rugosidad en el canal verde y metalicidad en el azul, como en glTF
+    pub metallic_roughness_map: Option<Arc<Texture>>,
+    // Mapa de normales en espacio tangente y su intensidad (-bm en MTL, scale en glTF)
+    pub normal_map: Option<Arc<Texture>>,
+    pub normal_scale: f32,
+}
+
+// Material usado por las mallas sin material propio (p. ej. las esferas de los planetas)
+pub static DEFAULT_SURFACE: SurfaceMaterial = SurfaceMaterial {
+    name: String::new(),
+    diffuse: Color::new(200, 200, 200),
+    specular: Color::new(0, 0, 0),
+    emissive: Color::new(0, 0, 0),
+    metallic: 0.0,
+    roughness: 1.0,
+    diffuse_map: None,
+    specular_map: None,
+    emissive_map: None,
+    metallic_roughness_map: None,
+    normal_map: None,
+    normal_scale: 1.0,
+};
+
+impl SurfaceMaterial {
+    pub fn diffuse_at(&self, uv: Vec2) -> Color {
+        modulate(self.diffuse, &self.diffuse_map, uv)
+    }
+
+    pub fn specular_at(&self, uv: Vec2) -> Color {
+        modulate(self.specular, &self.specular_map, uv)
+    }
+
+    pub fn emissive_at(&self, uv: Vec2) -> Color {
+        modulate(self.emissive, &self.emissive_map, uv)
+    }
+
+    pub fn metallic_roughness_at(&self, uv: Vec2) -> (f32, f32) {
+        match &self.metallic_roughness_map {
+            Some(texture) => {
+                let [_, roughness, metallic] = texture.sample(uv).to_rgb_f32();
+                (self.metallic * metallic, self.roughness * roughness)
+            }
+            None => (self.metallic, self.roughness),
+        }
+    }
+
+    // Normal del fragmento, perturbada por el mapa de normales si el material tiene uno
+    pub fn normal_at(&self, fragment: &Fragment) -> Vec3 {
+        match &self.normal_map {
+            Some(texture) => bump::mapped_normal(fragment, texture.sample(fragment.tex_coords), self.normal_scale),
+            None => fragment.normal,
+        }
+    }
+}
+
+// Conversión aproximada entre el exponente especular de Phong y la rugosidad
+pub fn roughness_from_shininess(shininess: f32) -> f32 {
+    (2.0 / (shininess.max(0.0) + 2.0)).sqrt()
+}
+
+pub fn shininess_from_roughness(roughness: f32) -> f32 {
+    let roughness = roughness.clamp(0.05, 1.0);
+    (2.0 / (roughness * roughness) - 2.0).max(1.0)
+}
+
+fn modulate(color: Color, map: &Option<Arc<Texture>>, uv: Vec2) -> Color {
+    match map {
+        Some(texture) => color * texture.sample(uv),
+        None => color,
+    }
+}
+
+// Modelo cargado de archivo: submallas que referencian sus materiales por índice
+pub struct Model {
+    pub source: String,
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<SurfaceMaterial>,
+    // Normales recalculadas a pedido en lugar de las del archivo
+    pub normals: Option<NormalMode>,
+}
+
+impl Model {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => obj::load(path),
+            Some("gltf") | Some("glb") => gltf_model::load(path),
+            _ => Err(format!("Formato de modelo no soportado: {}", path)),
+        }
+    }
+
+    pub fn recompute_normals(&mut self, mode: NormalMode) {
+        for mesh in self.meshes.iter_mut() {
+            mesh.compute_normals(mode);
+            mesh.compute_tangents();
+        }
+        self.normals = Some(mode);
+    }
+
+    pub fn material(&self, mesh: &Mesh) -> &SurfaceMaterial {
+        mesh.material
+            .and_then(|index| self.materials.get(index))
+            .unwrap_or(&DEFAULT_SURFACE)
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.meshes
+            .iter()
+            .flat_map(|mesh| &mesh.vertices)
+            .map(|vertex| vertex.position.norm())
+            .fold(0.0, f32::max)
+    }
+
+    // Une todas las submallas en una sola, descartando los materiales
+    pub fn into_mesh(self) -> Mesh {
+        let mut merged = Mesh::new(Vec::new(), Vec::new());
+        for mesh in self.meshes {
+            merged.append(mesh);
+        }
+        merged
+    }
+}
@@ -0,0 +1,273 @@
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{Vec3, Vec4};
+
+// Desfase entre octavas y entre los ejes del domain warping, para que no se correlacionen
+const OCTAVE_OFFSET: Vec3 = Vec3::new(17.31, -9.73, 23.59);
+const WARP_OFFSETS: [Vec3; 3] = [
+    Vec3::new(0.0, 0.0, 0.0),
+    Vec3::new(5.2, 1.3, -7.1),
+    Vec3::new(-3.7, 9.2, 2.8),
+];
+
+// Punto sobre la esfera unitaria. Los shaders muestrean el ruido en 3D sobre este punto,
+// así los patrones no se reflejan entre hemisferios ni se estiran cerca de los polos
+pub fn sphere_point(position: &Vec3) -> Vec3 {
+    position.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 1.0, 0.0))
+}
+
+// Ruido 3D en -1..1. Las coordenadas no pasan por la frecuencia de FastNoiseLite:
+// en todo el módulo un rasgo mide aproximadamente una unidad
+pub fn noise_3d(noise: &FastNoiseLite, point: &Vec3) -> f32 {
+    let point = point / noise.frequency;
+    noise.get_noise_3d(point.x, point.y, point.z)
+}
+
+pub fn noise_2d(noise: &FastNoiseLite, x: f32, y: f32) -> f32 {
+    noise.get_noise_2d(x / noise.frequency, y / noise.frequency)
+}
+
+// FastNoiseLite no tiene ruido 4D: se interpola entre cortes 3D independientes a lo largo de w
+// con una curva suave, lo que da un campo continuo en las cuatro coordenadas
+pub fn noise_4d(noise: &FastNoiseLite, point: &Vec4) -> f32 {
+    let slice = point.w.floor();
+    let blend = point.w - slice;
+    let blend = blend * blend * (3.0 - 2.0 * blend);
+    let at = |slice: f32| noise_3d(noise, &(point.xyz() + OCTAVE_OFFSET.zxy() * slice));
+    at(slice) * (1.0 - blend) + at(slice + 1.0) * blend
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FractalMode {
+    // Suma directa de octavas
+    Fbm,
+    // Suma de |n|: valles afilados y crestas redondeadas
+    Turbulence,
+    // Como Turbulence pero centrado, para nubes "algodonosas"
+    Billow,
+    // Multifractal con crestas (Musgrave): cada octava se pondera con la anterior
+    Ridged,
+}
+
+// Ruido fractal configurable. Todas las variantes devuelven valores en -1..1
+#[derive(Debug, Clone, Copy)]
+pub struct Fractal {
+    pub octaves: usize,
+    // Factor de frecuencia entre octavas
+    pub lacunarity: f32,
+    // Factor de amplitud entre octavas
+    pub gain: f32,
+    pub mode: FractalMode,
+}
+
+impl Fractal {
+    pub const fn fbm(octaves: usize) -> Self {
+        Fractal {
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+            mode: FractalMode::Fbm,
+        }
+    }
+
+    pub const fn ridged(octaves: usize) -> Self {
+        Fractal::fbm(octaves).with_mode(FractalMode::Ridged)
+    }
+
+    pub const fn with_mode(mut self, mode: FractalMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub const fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub const fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub fn sample_2d(&self, noise: &FastNoiseLite, x: f32, y: f32) -> f32 {
+        self.accumulate(|octave, frequency| {
+            let offset = OCTAVE_OFFSET * octave as f32;
+            noise_2d(noise, x * frequency + offset.x, y * frequency + offset.y)
+        })
+    }
+
+    pub fn sample_3d(&self, noise: &FastNoiseLite, point: &Vec3) -> f32 {
+        self.accumulate(|octave, frequency| noise_3d(noise, &(point * frequency + OCTAVE_OFFSET * octave as f32)))
+    }
+
+    // La cuarta coordenada suele ser el tiempo: el patrón cambia sin desplazarse
+    pub fn sample_4d(&self, noise: &FastNoiseLite, point: &Vec4) -> f32 {
+        self.accumulate(|octave, frequency| {
+            let offset = OCTAVE_OFFSET * octave as f32;
+            noise_4d(noise, &(point * frequency + Vec4::new(offset.x, offset.y, offset.z, octave as f32 * 0.37)))
+        })
+    }
+
+    // Suma las octavas según el modo y normaliza por la amplitud total
+    fn accumulate(&self, sample: impl Fn(usize, f32) -> f32) -> f32 {
+        let mut value = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut weight = 1.0;
+
+        for octave in 0..self.octaves {
+            let noise = sample(octave, frequency);
+            let signal = match self.mode {
+                FractalMode::Fbm => noise,
+                FractalMode::Turbulence => noise.abs(),
+                FractalMode::Billow => noise.abs() * 2.0 - 1.0,
+                FractalMode::Ridged => {
+                    let signal = (1.0 - noise.abs()).powi(2) * weight;
+                    weight = (signal * 2.0).clamp(0.0, 1.0);
+                    signal
+                }
+            };
+            value += signal * amplitude;
+            total += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let value = value / total;
+        // Turbulence y Ridged acumulan valores en 0..1; se llevan al mismo rango que el resto
+        let value = match self.mode {
+            FractalMode::Turbulence | FractalMode::Ridged => value * 2.0 - 1.0,
+            FractalMode::Fbm | FractalMode::Billow => value,
+        };
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+// Domain warping: desplaza el punto con un fractal por eje antes de muestrear el patrón final,
+// lo que produce formas arremolinadas (nubes, tormentas)
+pub fn warp(noise: &FastNoiseLite, point: &Vec3, strength: f32, fractal: &Fractal) -> Vec3 {
+    let offset = Vec3::new(
+        fractal.sample_3d(noise, &(point + WARP_OFFSETS[0])),
+        fractal.sample_3d(noise, &(point + WARP_OFFSETS[1])),
+        fractal.sample_3d(noise, &(point + WARP_OFFSETS[2])),
+    );
+    point + offset * strength
+}
+
+// Distancias al punto característico más cercano (f1) y al segundo (f2)
+#[derive(Debug, Clone, Copy)]
+pub struct Cells {
+    pub f1: f32,
+    pub f2: f32,
+}
+
+// Ruido celular de Worley con un punto característico por celda unitaria
+pub fn worley(noise: &FastNoiseLite, point: &Vec3) -> Cells {
+    let cell = point.map(f32::floor);
+    let mut cells = Cells { f1: f32::MAX, f2: f32::MAX };
+
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let neighbour = cell + Vec3::new(dx as f32, dy as f32, dz as f32);
+                let feature = neighbour + feature_offset(noise.seed, &neighbour);
+                let distance = (feature - point).norm();
+                if distance < cells.f1 {
+                    cells.f2 = cells.f1;
+                    cells.f1 = distance;
+                } else if distance < cells.f2 {
+                    cells.f2 = distance;
+                }
+            }
+        }
+    }
+    cells
+}
+
+// Posición pseudoaleatoria (0..1 por eje) del punto característico de una celda
+fn feature_offset(seed: i32, cell: &Vec3) -> Vec3 {
+    let mut hash = (seed as u32)
+        ^ (cell.x as i32 as u32).wrapping_mul(0x8DA6_B343)
+        ^ (cell.y as i32 as u32).wrapping_mul(0xD816_3841)
+        ^ (cell.z as i32 as u32).wrapping_mul(0xCB1A_B31F);
+    let mut next = || {
+        hash ^= hash >> 16;
+        hash = hash.wrapping_mul(0x7FEB_352D);
+        hash ^= hash >> 15;
+        hash = hash.wrapping_mul(0x846C_A68B);
+        hash ^= hash >> 16;
+        (hash & 0xFFFF) as f32 / 65536.0
+    };
+    Vec3::new(next(), next(), next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnoise_lite::NoiseType;
+
+    const MODES: [FractalMode; 4] = [FractalMode::Fbm, FractalMode::Turbulence, FractalMode::Billow, FractalMode::Ridged];
+
+    fn noise() -> FastNoiseLite {
+        let mut noise = FastNoiseLite::with_seed(1337);
+        noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+        noise
+    }
+
+    // Puntos repartidos en varias unidades de ruido y con coordenadas negativas
+    fn points() -> impl Iterator<Item = Vec4> {
+        (0..4000).map(|index| {
+            let index = index as f32;
+            Vec4::new(
+                (index * 0.618_034).fract() * 16.0 - 8.0,
+                (index * 0.754_878).fract() * 16.0 - 8.0,
+                (index * 0.569_840).fract() * 16.0 - 8.0,
+                (index * 0.412_454).fract() * 16.0 - 8.0,
+            )
+        })
+    }
+
+    fn samples(fractal: &Fractal) -> [Vec<f32>; 3] {
+        let noise = noise();
+        [
+            points().map(|point| fractal.sample_2d(&noise, point.x, point.y)).collect(),
+            points().map(|point| fractal.sample_3d(&noise, &point.xyz())).collect(),
+            points().map(|point| fractal.sample_4d(&noise, &point)).collect(),
+        ]
+    }
+
+    // El rango sale de la normalización por la amplitud total y no del clamp final: casi ningún
+    // valor llega a los extremos y se usa buena parte del intervalo
+    fn assert_range(fractal: &Fractal) {
+        for (dimension, values) in samples(fractal).iter().enumerate() {
+            let label = format!("{:?} {}D, {} octavas", fractal.mode, dimension + 2, fractal.octaves);
+            let min = values.iter().copied().fold(f32::MAX, f32::min);
+            let max = values.iter().copied().fold(f32::MIN, f32::max);
+            let saturated = values.iter().filter(|value| value.abs() >= 1.0).count();
+            assert!(min >= -1.0 && max <= 1.0, "{}: {}..{}", label, min, max);
+            assert!(saturated * 200 < values.len(), "{}: {} valores saturados", label, saturated);
+            assert!(max - min > 0.5, "{}: rango demasiado estrecho {}..{}", label, min, max);
+        }
+    }
+
+    #[test]
+    fn every_mode_stays_in_range() {
+        for mode in MODES {
+            for octaves in [1, 3, 6] {
+                assert_range(&Fractal::fbm(octaves).with_mode(mode));
+            }
+            assert_range(&Fractal::fbm(5).with_mode(mode).with_gain(0.8).with_lacunarity(2.2));
+        }
+    }
+
+    #[test]
+    fn fbm_is_centered() {
+        for values in samples(&Fractal::fbm(6)) {
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+            assert!(mean.abs() < 0.05, "media {}", mean);
+        }
+    }
+}
@@ -1,62 +1,142 @@
-use tobj;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
+use crate::mesh::{Mesh, NormalMode};
+use crate::model::{roughness_from_shininess, Model, SurfaceMaterial};
+use crate::texture::Texture;
 use crate::vertex::Vertex;
 
-pub struct Obj {
-    meshes: Vec<Mesh>,
-}
+pub fn load(filename: &str) -> Result<Model, String> {
+    let (models, materials) = tobj::load_obj(filename, &tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ..Default::default()
+    })
+    .map_err(|err| format!("No se pudo cargar {}: {}", filename, err))?;
 
-struct Mesh {
-    vertices: Vec<Vec3>,
-    normals: Vec<Vec3>,
-    texcoords: Vec<Vec2>,
-    indices: Vec<u32>,
-}
+    // Un MTL ausente o inválido no impide usar la geometría
+    let materials = materials.unwrap_or_else(|err| {
+        eprintln!("{}: no se pudieron cargar los materiales: {}", filename, err);
+        Vec::new()
+    });
+    let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let mut textures = TextureCache::new(directory);
+    let materials = materials
+        .iter()
+        .map(|material| surface_material(material, &mut textures))
+        .collect();
 
-impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
-
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
+    let meshes = models.into_iter().map(|model| {
+        let mesh = model.mesh;
+        let vertices = mesh.positions.chunks(3)
+            .enumerate()
+            .map(|(index, p)| {
+                let position = Vec3::new(p[0], p[1], p[2]);
+                let normal = mesh.normals.get(index * 3..index * 3 + 3)
                     .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
+                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                let tex_coords = mesh.texcoords.get(index * 2..index * 2 + 2)
                     .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
-            }
-        }).collect();
+                    .unwrap_or(Vec2::new(0.0, 0.0));
+                Vertex::new(position, normal, tex_coords)
+            })
+            .collect();
+        let mut generated = Mesh::new(vertices, mesh.indices).with_material(mesh.material_id);
+        if mesh.normals.is_empty() {
+            generated.compute_normals(NormalMode::Smooth);
+        }
+        generated.compute_tangents();
+        generated
+    }).collect();
 
-        Ok(Obj { meshes })
+    Ok(Model {
+        source: filename.to_string(),
+        meshes,
+        materials,
+        normals: None,
+    })
+}
+
+fn surface_material(material: &tobj::Material, textures: &mut TextureCache) -> SurfaceMaterial {
+    let color = |rgb: Option<[f32; 3]>, default: Color| rgb.map(Color::from_rgb_f32).unwrap_or(default);
+    // Ke y map_Ke no forman parte del MTL original; tobj los deja como parámetros desconocidos
+    let emissive = material
+        .unknown_param
+        .get("Ke")
+        .and_then(|value| parse_rgb(value))
+        .map(Color::from_rgb_f32)
+        .unwrap_or(Color::black());
+    // map_Bump y bump se leen como mapa de normales en espacio tangente
+    let normal_texture = material.normal_texture.as_ref();
+
+    SurfaceMaterial {
+        name: material.name.clone(),
+        diffuse: color(material.diffuse, Color::new(200, 200, 200)),
+        specular: color(material.specular, Color::black()),
+        emissive,
+        // Pm y Pr (extensión PBR del MTL) tienen prioridad sobre Ns
+        metallic: param(material, "Pm").unwrap_or(0.0).clamp(0.0, 1.0),
+        roughness: param(material, "Pr")
+            .unwrap_or_else(|| roughness_from_shininess(material.shininess.unwrap_or(1.0)))
+            .clamp(0.0, 1.0),
+        diffuse_map: textures.get(material.diffuse_texture.as_ref()),
+        specular_map: textures.get(material.specular_texture.as_ref()),
+        emissive_map: textures.get(material.unknown_param.get("map_Ke")),
+        metallic_roughness_map: None,
+        normal_map: textures.get(normal_texture),
+        normal_scale: normal_texture.and_then(|value| option_value(value, "-bm")).unwrap_or(1.0),
     }
+}
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
+fn param(material: &tobj::Material, key: &str) -> Option<f32> {
+    material.unknown_param.get(key)?.trim().parse().ok()
+}
 
-        for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
+// Valor numérico de una opción de mapa, p. ej. "-bm 0.5 archivo.png"
+fn option_value(value: &str, option: &str) -> Option<f32> {
+    let mut tokens = value.split_whitespace();
+    tokens.find(|token| *token == option)?;
+    tokens.next()?.parse().ok()
+}
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
-            }
+fn parse_rgb(value: &str) -> Option<[f32; 3]> {
+    let channels: Vec<f32> = value.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+    match channels[..] {
+        [r, g, b] => Some([r, g, b]),
+        [gray] => Some([gray; 3]),
+        _ => None,
+    }
+}
+
+// Las rutas de textura son relativas al archivo del modelo; cada archivo se carga una sola vez
+struct TextureCache<'a> {
+    directory: &'a Path,
+    loaded: HashMap<PathBuf, Option<Arc<Texture>>>,
+}
+
+impl<'a> TextureCache<'a> {
+    fn new(directory: &'a Path) -> Self {
+        TextureCache {
+            directory,
+            loaded: HashMap::new(),
         }
+    }
 
-        vertices
+    fn get(&mut self, name: Option<&String>) -> Option<Arc<Texture>> {
+        // Las opciones de mapa (-bm, -o, ...) preceden al nombre del archivo
+        let file = name?.split_whitespace().last()?;
+        let path = self.directory.join(file);
+        self.loaded
+            .entry(path)
+            .or_insert_with_key(|path| match Texture::load(path) {
+                Ok(texture) => Some(Arc::new(texture)),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    None
+                }
+            })
+            .clone()
     }
 }
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+use nalgebra_glm::{distance, Vec3};
+use crate::framebuffer::Framebuffer;
+use crate::line::{draw_line, LineProjection};
+use crate::planet::{self, Planet};
+
+const ORBIT_SEGMENTS: usize = 128;
+const TRAIL_LENGTH: usize = 90;
+const ORBIT_COLOR: u32 = 0x4060A0;
+const SELECTED_ORBIT_COLOR: u32 = 0x00FFFF;
+const TRAIL_COLOR: u32 = 0xFFE0A0;
+
+pub struct OrbitRenderer {
+    pub show_orbits: bool,
+    pub show_trails: bool,
+    trails: Vec<VecDeque<Vec3>>,
+}
+
+impl OrbitRenderer {
+    pub fn new() -> Self {
+        OrbitRenderer {
+            show_orbits: true,
+            show_trails: true,
+            trails: Vec::new(),
+        }
+    }
+
+    // Guarda la posición de cada cuerpo cuando avanzó lo suficiente desde el último punto.
+    // Se guarda relativa al centro de su órbita para que la estela de una luna acompañe a su planeta
+    pub fn record(&mut self, planets: &[Planet]) {
+        self.trails.resize_with(planets.len(), VecDeque::new);
+        for (trail, body) in self.trails.iter_mut().zip(planets) {
+            let spacing = orbit_segment_length(body);
+            if spacing <= f32::EPSILON {
+                trail.clear();
+                continue;
+            }
+            let position = body.position - planet::orbit_center(planets, body);
+            let moved = trail.back().map(|last| distance(last, &position));
+            match moved {
+                // Un salto grande (p. ej. al recargar la escena) reinicia la estela
+                Some(moved) if moved > spacing * 4.0 => trail.clear(),
+                Some(moved) if moved < spacing => continue,
+                _ => {}
+            }
+            if trail.len() == TRAIL_LENGTH {
+                trail.pop_front();
+            }
+            trail.push_back(position);
+        }
+    }
+
+    pub fn draw(
+        &self,
+        framebuffer: &mut Framebuffer,
+        planets: &[Planet],
+        selected: Option<usize>,
+        projection: &LineProjection,
+        antialiased: bool,
+    ) {
+        if self.show_orbits {
+            for (index, body) in planets.iter().enumerate() {
+                let (color, alpha) = if selected == Some(index) {
+                    (SELECTED_ORBIT_COLOR, 0.8)
+                } else {
+                    (ORBIT_COLOR, 0.5)
+                };
+                let points = orbit_points(body, planet::orbit_center(planets, body));
+                for segment in points.windows(2) {
+                    draw_segment(framebuffer, &segment[0], &segment[1], projection, color, alpha, antialiased);
+                }
+            }
+        }
+
+        if self.show_trails {
+            for (trail, body) in self.trails.iter().zip(planets) {
+                let center = planet::orbit_center(planets, body);
+                let points: Vec<Vec3> = trail.iter().map(|point| center + point).collect();
+                for (index, segment) in points.windows(2).enumerate() {
+                    // Los puntos más antiguos se desvanecen
+                    let alpha = (index + 1) as f32 / TRAIL_LENGTH as f32;
+                    draw_segment(framebuffer, &segment[0], &segment[1], projection, TRAIL_COLOR, alpha, antialiased);
+                }
+            }
+        }
+    }
+}
+
+fn orbit_segment_length(planet: &Planet) -> f32 {
+    planet.orbit_radius.abs() * TAU / ORBIT_SEGMENTS as f32
+}
+
+// La órbita empieza en la posición actual para que pase exactamente por el cuerpo.
+// Se recorre en anomalía excéntrica, que reparte los puntos mejor que la media en órbitas alargadas
+fn orbit_points(planet: &Planet, center: Vec3) -> Vec<Vec3> {
+    if orbit_segment_length(planet) <= f32::EPSILON {
+        return Vec::new();
+    }
+    let start = planet.eccentric_anomaly();
+    (0..=ORBIT_SEGMENTS)
+        .map(|segment| center + planet.orbit_point(start + TAU * segment as f32 / ORBIT_SEGMENTS as f32))
+        .collect()
+}
+
+fn draw_segment(
+    framebuffer: &mut Framebuffer,
+    a: &Vec3,
+    b: &Vec3,
+    projection: &LineProjection,
+    color: u32,
+    alpha: f32,
+    antialiased: bool,
+) {
+    if let Some((start, end)) = projection.project_segment(a, b) {
+        draw_line(framebuffer, start, end, color, alpha, antialiased);
+    }
+}
@@ -0,0 +1,168 @@
+use minifb::{Key, KeyRepeat, Window};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::scene::Scene;
+use crate::shaders::ShaderParam;
+use crate::text::{draw_text, LINE_HEIGHT};
+
+const VISIBLE_ENTRIES: usize = 16;
+
+pub enum PanelAction {
+    None,
+    Save,
+}
+
+// Un parámetro editable; los colores se dividen en un canal por entrada
+struct Entry {
+    name: &'static str,
+    channel: Option<usize>,
+    value: ShaderParam,
+}
+
+pub struct ParamPanel {
+    pub visible: bool,
+    entry: usize,
+    pub message: Option<String>,
+}
+
+impl ParamPanel {
+    pub fn new() -> Self {
+        ParamPanel {
+            visible: false,
+            entry: 0,
+            message: None,
+        }
+    }
+
+    pub fn handle_input(&mut self, window: &Window, scene: &mut Scene, selected: Option<usize>) -> PanelAction {
+        if window.is_key_pressed(Key::F1, KeyRepeat::No) {
+            self.visible = !self.visible;
+        }
+        let Some(body) = selected.filter(|body| self.visible && *body < scene.planets.len()) else {
+            return PanelAction::None;
+        };
+
+        let entries = entries(scene, body);
+        if entries.is_empty() {
+            return PanelAction::None;
+        }
+        if window.is_key_pressed(Key::PageDown, KeyRepeat::Yes) {
+            self.entry = (self.entry + 1) % entries.len();
+        }
+        if window.is_key_pressed(Key::PageUp, KeyRepeat::Yes) {
+            self.entry = (self.entry + entries.len() - 1) % entries.len();
+        }
+        self.entry = self.entry.min(entries.len() - 1);
+
+        let direction = if window.is_key_pressed(Key::Equal, KeyRepeat::Yes) {
+            1.0
+        } else if window.is_key_pressed(Key::Minus, KeyRepeat::Yes) {
+            -1.0
+        } else {
+            0.0
+        };
+        if direction != 0.0 {
+            let entry = &entries[self.entry];
+            let material = &mut scene.planets[body].material;
+            if let Err(err) = material.set_param_value(entry.name, nudge(entry, direction)) {
+                self.message = Some(err);
+            }
+        }
+
+        if window.is_key_pressed(Key::F2, KeyRepeat::No) {
+            return PanelAction::Save;
+        }
+        PanelAction::None
+    }
+
+    pub fn draw(&self, output: &mut Framebuffer, scene: &Scene, selected: Option<usize>) {
+        if !self.visible {
+            return;
+        }
+
+        let x = 10;
+        let y = 10;
+        let width = 300;
+        let height = LINE_HEIGHT * (VISIBLE_ENTRIES + 5) + 10;
+        output.blend_rect(x, y, width, height, 0x000000, 0.7);
+
+        let text_x = x + 8;
+        let mut line_y = y + 8;
+
+        let Some((body, planet)) = selected.and_then(|body| Some((body, scene.planets.get(body)?))) else {
+            draw_text(output, text_x, line_y, "Selecciona un cuerpo (Tab)", 0xFFFFFF, 1);
+            return;
+        };
+        let title = format!("{} ({})", planet.name, planet.material.fragment_shader.name());
+        draw_text(output, text_x, line_y, &title, 0xFFD700, 1);
+        line_y += LINE_HEIGHT * 2;
+
+        let entries = entries(scene, body);
+        let first = self.entry.saturating_sub(VISIBLE_ENTRIES / 2).min(entries.len().saturating_sub(VISIBLE_ENTRIES));
+        for (index, entry) in entries.iter().enumerate().skip(first).take(VISIBLE_ENTRIES) {
+            let selected = index == self.entry;
+            let line = format!("{} {} = {}", if selected { ">" } else { " " }, label(entry), value_text(entry));
+            draw_text(output, text_x, line_y, &line, if selected { 0xFFFFFF } else { 0xA0A0A0 }, 1);
+            line_y += LINE_HEIGHT;
+        }
+
+        let footer_y = y + height - LINE_HEIGHT * 2 - 4;
+        if let Some(message) = &self.message {
+            draw_text(output, text_x, footer_y - LINE_HEIGHT, message, 0x80FF80, 1);
+        }
+        draw_text(output, text_x, footer_y, "Tab cuerpo  RePag/AvPag parametro", 0x808080, 1);
+        draw_text(output, text_x, footer_y + LINE_HEIGHT, "-/+ ajustar  F2 guardar  F1 ocultar", 0x808080, 1);
+    }
+}
+
+fn entries(scene: &Scene, body: usize) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for (name, value) in scene.planets[body].material.params() {
+        match value {
+            ShaderParam::Float(_) => entries.push(Entry { name, channel: None, value }),
+            ShaderParam::Color(_) => {
+                for channel in 0..3 {
+                    entries.push(Entry { name, channel: Some(channel), value });
+                }
+            }
+        }
+    }
+    entries
+}
+
+fn channel_shift(channel: usize) -> u32 {
+    16 - 8 * channel as u32
+}
+
+fn label(entry: &Entry) -> String {
+    match entry.channel {
+        Some(channel) => format!("{}.{}", entry.name, ["r", "g", "b"][channel]),
+        None => entry.name.to_string(),
+    }
+}
+
+fn value_text(entry: &Entry) -> String {
+    match (entry.value, entry.channel) {
+        (ShaderParam::Color(color), Some(channel)) => {
+            format!("{}", (color.to_hex() >> channel_shift(channel)) & 0xFF)
+        }
+        (value, _) => format!("{}", value),
+    }
+}
+
+fn nudge(entry: &Entry, direction: f32) -> ShaderParam {
+    match (entry.value, entry.channel) {
+        (ShaderParam::Float(value), _) => {
+            let step = (value.abs() * 0.05).max(0.01);
+            ShaderParam::Float(value + step * direction)
+        }
+        (ShaderParam::Color(color), Some(channel)) => {
+            let shift = channel_shift(channel);
+            let hex = color.to_hex();
+            let current = ((hex >> shift) & 0xFF) as f32;
+            let updated = (current + 5.0 * direction).clamp(0.0, 255.0) as u32;
+            ShaderParam::Color(Color::from_hex((hex & !(0xFF << shift)) | (updated << shift)))
+        }
+        (value, None) => value,
+    }
+}
@@ -0,0 +1,319 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::generator::Rng;
+use crate::line::LineProjection;
+use crate::lod;
+use crate::planet::Planet;
+
+// Límite por emisor, para que acelerar el reloj no dispare la cantidad de partículas
+const MAX_PARTICLES: usize = 4000;
+const TAIL_SPEED: f32 = 0.02;
+// Gravedad de las protuberancias, proporcional al radio de la estrella
+const FLARE_GRAVITY: f32 = 6.0e-5;
+const ACTIVE_FLARES: usize = 3;
+// Distancia al Sol a la que un cometa alcanza su actividad nominal, si la escena no da otra
+pub const DEFAULT_ACTIVITY_DISTANCE: f32 = 40.0;
+// Tope de actividad en el perihelio, para que la cola no crezca sin límite
+const MAX_ACTIVITY: f32 = 4.0;
+// Radio de la coma en radios del núcleo con actividad nominal
+const COMA_SIZE: f32 = 10.0;
+const COMA_INTENSITY: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleKind {
+    // Cola que se aleja del Sol
+    Tail,
+    // Protuberancias: arcos de plasma que salen de la superficie y vuelven a caer
+    Flares,
+}
+
+impl ParticleKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tail" => Some(ParticleKind::Tail),
+            "flares" => Some(ParticleKind::Flares),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ParticleKind::Tail => "tail",
+            ParticleKind::Flares => "flares",
+        }
+    }
+
+    fn settings(self) -> &'static EmitterSettings {
+        match self {
+            ParticleKind::Tail => &TAIL,
+            ParticleKind::Flares => &FLARES,
+        }
+    }
+}
+
+// Evolución de cada tipo de partícula; los tamaños son relativos al radio del cuerpo
+struct EmitterSettings {
+    // Partículas por unidad de tiempo de simulación
+    rate: f32,
+    lifetime: (f32, f32),
+    start_color: Color,
+    end_color: Color,
+    start_size: f32,
+    end_size: f32,
+    intensity: f32,
+}
+
+const TAIL: EmitterSettings = EmitterSettings {
+    rate: 6.0,
+    lifetime: (120.0, 220.0),
+    start_color: Color::new(200, 230, 255),
+    end_color: Color::new(60, 90, 200),
+    start_size: 0.5,
+    end_size: 2.5,
+    intensity: 0.2,
+};
+
+const FLARES: EmitterSettings = EmitterSettings {
+    rate: 8.0,
+    lifetime: (300.0, 400.0),
+    start_color: Color::new(255, 220, 120),
+    end_color: Color::new(255, 60, 20),
+    start_size: 0.06,
+    end_size: 0.03,
+    intensity: 0.12,
+};
+
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+}
+
+// Punto de la superficie del que sale una protuberancia mientras dura
+struct Flare {
+    normal: Vec3,
+    tangent: Vec3,
+    height: f32,
+    remaining: f32,
+}
+
+pub struct ParticleEmitter {
+    pub kind: ParticleKind,
+    particles: Vec<Particle>,
+    flares: Vec<Flare>,
+    // Fracción de partícula pendiente entre frames
+    pending: f32,
+    // Radio y posición del cuerpo en la última actualización, para escalar los tamaños y dibujar la coma
+    radius: f32,
+    center: Vec3,
+    // Las colas se activan al acercarse al Sol: la actividad es 1 a esta distancia y crece con el
+    // inverso del cuadrado de la distancia, como la luz que sublima el hielo del núcleo
+    pub activity_distance: f32,
+    activity: f32,
+    rng: Rng,
+}
+
+impl ParticleEmitter {
+    pub fn new(kind: ParticleKind, seed: u64) -> Self {
+        ParticleEmitter {
+            kind,
+            particles: Vec::new(),
+            flares: Vec::new(),
+            pending: 0.0,
+            radius: 0.0,
+            center: Vec3::zeros(),
+            activity_distance: DEFAULT_ACTIVITY_DISTANCE,
+            activity: 1.0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    // Conserva la configuración recargada y toma de `previous` las partículas vivas y el estado de emisión
+    pub fn continue_from(&mut self, previous: ParticleEmitter) {
+        self.particles = previous.particles;
+        self.flares = previous.flares;
+        self.pending = previous.pending;
+        self.radius = previous.radius;
+        self.center = previous.center;
+        self.activity = previous.activity;
+        self.rng = previous.rng;
+    }
+
+    // Integra las partículas vivas en paralelo y emite las nuevas desde el cuerpo en `center`;
+    // `sun` es la posición de la estrella, hacia la que se orientan las colas
+    pub fn update(&mut self, delta_time: f32, center: Vec3, radius: f32, sun: Vec3) {
+        if delta_time <= 0.0 {
+            return;
+        }
+        self.radius = radius;
+        self.center = center;
+        let from_sun = center - sun;
+        if self.kind == ParticleKind::Tail {
+            let distance = from_sun.norm().max(f32::EPSILON);
+            self.activity = (self.activity_distance / distance).powi(2).min(MAX_ACTIVITY);
+        }
+        let gravity = match self.kind {
+            ParticleKind::Tail => 0.0,
+            ParticleKind::Flares => FLARE_GRAVITY * radius,
+        };
+        self.particles.par_iter_mut().for_each(|particle| {
+            let offset = particle.position - center;
+            if gravity > 0.0 {
+                particle.velocity -= offset.try_normalize(f32::EPSILON).unwrap_or(Vec3::zeros()) * gravity * delta_time;
+            }
+            particle.position += particle.velocity * delta_time;
+            particle.age += delta_time;
+            // Las protuberancias terminan al volver a caer sobre la superficie
+            if gravity > 0.0 && particle.velocity.dot(&offset) < 0.0 && offset.norm() < radius {
+                particle.age = particle.lifetime;
+            }
+        });
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        let settings = self.kind.settings();
+        self.pending += settings.rate * self.activity * delta_time;
+        let count = (self.pending as usize).min(MAX_PARTICLES - self.particles.len());
+        self.pending = self.pending.fract();
+        for _ in 0..count {
+            let particle = match self.kind {
+                ParticleKind::Tail => self.spawn_tail(center, radius, from_sun),
+                ParticleKind::Flares => self.spawn_flare(center, radius, gravity),
+            };
+            self.particles.push(particle);
+        }
+
+        for flare in self.flares.iter_mut() {
+            flare.remaining -= delta_time;
+        }
+        self.flares.retain(|flare| flare.remaining > 0.0);
+    }
+
+    // La cola apunta en dirección opuesta al Sol (`from_sun`); las partículas que quedan atrás
+    // mientras el cuerpo avanza curvan la parte de polvo. Cerca del Sol salen más rápido y la cola se alarga
+    fn spawn_tail(&mut self, center: Vec3, radius: f32, from_sun: Vec3) -> Particle {
+        let away = from_sun.try_normalize(f32::EPSILON).unwrap_or(Vec3::x());
+        let speed = TAIL_SPEED * self.activity.sqrt();
+        let velocity = away * speed * self.rng.range(0.6, 1.0) + self.random_direction() * speed * 0.15;
+        Particle {
+            position: center + self.random_direction() * radius,
+            velocity,
+            age: 0.0,
+            lifetime: self.lifetime(),
+        }
+    }
+
+    // Tiro parabólico desde un punto de la superficie: la altura del arco fija la velocidad de salida
+    fn spawn_flare(&mut self, center: Vec3, radius: f32, gravity: f32) -> Particle {
+        while self.flares.len() < ACTIVE_FLARES {
+            let normal = self.random_direction();
+            let tangent = normal.cross(&self.random_direction()).try_normalize(f32::EPSILON).unwrap_or(normal.yzx());
+            self.flares.push(Flare {
+                normal,
+                tangent,
+                height: radius * self.rng.range(0.15, 0.4),
+                remaining: self.rng.range(80.0, 240.0),
+            });
+        }
+        let index = self.rng.below(self.flares.len());
+        let (normal, tangent, height) = {
+            let flare = &self.flares[index];
+            (flare.normal, flare.tangent, flare.height)
+        };
+        let up = (2.0 * gravity * height).sqrt() * self.rng.range(0.9, 1.1);
+        let footpoint = (normal + self.random_direction() * 0.03).normalize();
+        Particle {
+            position: center + footpoint * radius,
+            velocity: footpoint * up + tangent * up * 0.4,
+            age: 0.0,
+            lifetime: self.lifetime(),
+        }
+    }
+
+    fn lifetime(&mut self) -> f32 {
+        let (min, max) = self.kind.settings().lifetime;
+        self.rng.range(min, max)
+    }
+
+    fn random_direction(&mut self) -> Vec3 {
+        let direction = Vec3::new(self.rng.range(-1.0, 1.0), self.rng.range(-1.0, 1.0), self.rng.range(-1.0, 1.0));
+        direction.try_normalize(f32::EPSILON).unwrap_or(Vec3::y())
+    }
+}
+
+// Manchas difusas ya proyectadas: posición en pantalla con profundidad, radio en píxeles y color
+struct Splat {
+    screen: Vec3,
+    radius: f32,
+    color: u32,
+    intensity: f32,
+}
+
+// Mezcla aditiva sobre el buffer resuelto, con prueba de profundidad contra la escena.
+// La proyección de las partículas se reparte entre hilos; la suma en el buffer es secuencial
+pub fn draw(framebuffer: &mut Framebuffer, planets: &[Planet], projection: &LineProjection, eye: &Vec3, fov_y: f32) {
+    let height = framebuffer.height as f32;
+    for emitter in planets.iter().filter_map(|planet| planet.emitter.as_ref()) {
+        let settings = emitter.kind.settings();
+        let splats: Vec<Splat> = emitter
+            .particles
+            .par_iter()
+            .filter_map(|particle| {
+                let screen = projection.project_point(&particle.position)?;
+                let life = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+                let size = emitter.radius * (settings.start_size + (settings.end_size - settings.start_size) * life);
+                let distance = nalgebra_glm::distance(eye, &particle.position);
+                Some(Splat {
+                    screen,
+                    radius: lod::projected_radius(size, distance, fov_y, height),
+                    color: settings.start_color.lerp(&settings.end_color, life).to_hex(),
+                    intensity: settings.intensity * (1.0 - life),
+                })
+            })
+            .collect();
+
+        for splat in splats {
+            framebuffer.add_splat(splat.screen, splat.radius, splat.color, splat.intensity);
+        }
+
+        if emitter.kind == ParticleKind::Tail {
+            draw_coma(framebuffer, emitter, projection, eye, fov_y);
+        }
+    }
+}
+
+// Nube difusa alrededor del núcleo que crece con la actividad. Se centra en el punto del núcleo más
+// cercano a la cámara para que la prueba de profundidad no la esconda detrás del propio núcleo
+fn draw_coma(framebuffer: &mut Framebuffer, emitter: &ParticleEmitter, projection: &LineProjection, eye: &Vec3, fov_y: f32) {
+    let toward_eye = (eye - emitter.center).try_normalize(f32::EPSILON).unwrap_or(Vec3::z());
+    let front = emitter.center + toward_eye * emitter.radius;
+    if let Some(screen) = projection.project_point(&front) {
+        let size = emitter.radius * COMA_SIZE * emitter.activity.sqrt();
+        let radius = lod::projected_radius(size, nalgebra_glm::distance(eye, &front), fov_y, framebuffer.height as f32);
+        framebuffer.add_splat(screen, radius, TAIL.start_color.to_hex(), COMA_INTENSITY * emitter.activity.min(1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // La estrella no tiene por qué estar en el origen
+    #[test]
+    fn tail_points_away_from_the_sun() {
+        let sun = Vec3::new(100.0, 0.0, 0.0);
+        let comet = Vec3::new(100.0, 0.0, -20.0);
+        let mut emitter = ParticleEmitter::new(ParticleKind::Tail, 3);
+        for _ in 0..60 {
+            emitter.update(1.0, comet, 0.1, sun);
+        }
+
+        assert!((emitter.activity - (DEFAULT_ACTIVITY_DISTANCE / 20.0).powi(2).min(MAX_ACTIVITY)).abs() < 1e-5);
+        assert!(!emitter.particles.is_empty());
+        let drift: Vec3 = emitter.particles.iter().map(|particle| particle.velocity).sum();
+        let away = (comet - sun).normalize();
+        assert!(drift.normalize().dot(&away) > 0.95);
+    }
+}
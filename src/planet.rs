@@ -1,37 +1,182 @@
+use std::f32::consts::{PI, TAU};
 use nalgebra_glm::{ Vec3,Mat4};
+use std::sync::Arc;
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::model::Model;
+use crate::particles::ParticleEmitter;
+use crate::sphere;
 
+const RING_SEGMENTS: u32 = 96;
+// Iteraciones de Newton para la ecuación de Kepler; con e < 0.99 converge en menos de 10
+const KEPLER_ITERATIONS: usize = 12;
+// Inclinación de los anillos respecto al plano orbital
+const RING_TILT: f32 = 0.45;
+
+// Anillos planos alrededor del cuerpo; los radios están en unidades del modelo (la esfera mide 0.5)
+pub struct Rings {
+    pub inner: f32,
+    pub outer: f32,
+    pub mesh: Mesh,
+}
+
+impl Rings {
+    pub fn new(inner: f32, outer: f32) -> Self {
+        Rings {
+            inner,
+            outer,
+            mesh: sphere::ring(inner, outer, RING_SEGMENTS, RING_TILT),
+        }
+    }
+}
 
 pub struct Planet {
+    pub name: String,
     pub position: Vec3,
+    // Semieje mayor; en una órbita circular es el radio
     pub orbit_radius: f32,
     pub orbit_speed: f32,
+    // Anomalía media: avanza a ritmo constante aunque el cuerpo no lo haga
     pub current_angle: f32,
+    pub eccentricity: f32,
+    // Orientación de la órbita en grados: inclinación sobre el plano orbital, argumento del
+    // periastro y longitud del nodo ascendente
+    pub inclination: f32,
+    pub periapsis: f32,
+    pub ascending_node: f32,
     pub size: f32,
-    pub shader_index: usize,
+    pub material: Material,
+    pub model: Option<Arc<Model>>,
+    // Índice del cuerpo alrededor del cual orbita (siempre anterior en la escena)
+    pub parent: Option<usize>,
+    pub rings: Option<Rings>,
+    pub emitter: Option<ParticleEmitter>,
 }
 
 impl Planet {
-    pub fn new(orbit_radius: f32, orbit_speed: f32, size: f32, shader_index: usize) -> Self {
+    pub fn new(name: &str, orbit_radius: f32, orbit_speed: f32, size: f32, material: Material) -> Self {
         Planet {
+            name: name.to_string(),
             position: Vec3::zeros(),
             orbit_radius,
             orbit_speed,
             current_angle: 0.0,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            periapsis: 0.0,
+            ascending_node: 0.0,
             size,
-            shader_index,
+            material,
+            model: None,
+            parent: None,
+            rings: None,
+            emitter: None,
         }
     }
 
-    pub fn update_position(&mut self, delta_time: f32) {
+    pub fn update_position(&mut self, delta_time: f32, center: Vec3) {
         self.current_angle += self.orbit_speed * delta_time;
-        self.position = Vec3::new(
-            self.orbit_radius * self.current_angle.cos(),
-            0.0,
-            self.orbit_radius * self.current_angle.sin(),
-        );
+        self.position = center + self.orbit_point(self.eccentric_anomaly());
+    }
+
+    // Resuelve la ecuación de Kepler (M = E - e·sen E) para la anomalía media actual
+    pub fn eccentric_anomaly(&self) -> f32 {
+        let mean = self.current_angle.rem_euclid(TAU);
+        let e = self.eccentricity;
+        // Con excentricidades altas, empezar en π evita que Newton oscile cerca del periastro
+        let mut anomaly = if e < 0.8 { mean } else { PI };
+        for _ in 0..KEPLER_ITERATIONS {
+            let step = (anomaly - e * anomaly.sin() - mean) / (1.0 - e * anomaly.cos());
+            anomaly -= step;
+            if step.abs() < 1e-6 {
+                break;
+            }
+        }
+        anomaly
+    }
+
+    // Posición relativa al centro de la órbita para una anomalía excéntrica dada.
+    // Con excentricidad e inclinación nulas es el círculo de siempre en el plano XZ. El norte del
+    // plano orbital es -Y: así ese círculo gira en sentido directo y las efemérides no quedan reflejadas
+    pub fn orbit_point(&self, eccentric_anomaly: f32) -> Vec3 {
+        let e = self.eccentricity;
+        let x = self.orbit_radius * (eccentric_anomaly.cos() - e);
+        let y = self.orbit_radius * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+        let (sin_w, cos_w) = self.periapsis.to_radians().sin_cos();
+        let (sin_i, cos_i) = self.inclination.to_radians().sin_cos();
+        let (sin_n, cos_n) = self.ascending_node.to_radians().sin_cos();
+        // Periastro dentro del plano de la órbita, inclinación alrededor de la línea de nodos
+        // y luego el giro del nodo sobre el plano de referencia
+        let (x, y) = (x * cos_w - y * sin_w, x * sin_w + y * cos_w);
+        let (y, height) = (y * cos_i, y * sin_i);
+        Vec3::new(x * cos_n - y * sin_n, -height, x * sin_n + y * cos_n)
     }
 
     pub fn get_model_matrix(&self) -> Mat4 {
         Mat4::new_translation(&self.position) * Mat4::new_scaling(self.size)
     }
 }
+
+// Centro de la órbita de un cuerpo: la posición de su padre o el origen
+pub fn orbit_center(planets: &[Planet], planet: &Planet) -> Vec3 {
+    planet
+        .parent
+        .and_then(|parent| planets.get(parent))
+        .map_or(Vec3::zeros(), |parent| parent.position)
+}
+
+// Los padres van antes que sus satélites, así cada luna usa la posición ya actualizada de su planeta
+pub fn update_positions(planets: &mut [Planet], delta_time: f32) {
+    for index in 0..planets.len() {
+        let center = orbit_center(planets, &planets[index]);
+        planets[index].update_position(delta_time, center);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).norm() < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+
+    // El norte del plano orbital es -Y: con el periastro a 90° del nodo ascendente, el cuerpo
+    // pasa por él al norte del plano
+    #[test]
+    fn inclined_orbit_rises_toward_negative_y() {
+        let mut planet = Planet::new("Prueba", 10.0, 0.0, 1.0, Material::new("icy").unwrap());
+        planet.inclination = 30.0;
+        planet.periapsis = 90.0;
+        assert_near(planet.orbit_point(0.0), Vec3::new(0.0, -5.0, 8.660254));
+        // Sin inclinación el cuerpo avanza de +X hacia +Z, como las órbitas circulares
+        planet.inclination = 0.0;
+        planet.periapsis = 0.0;
+        assert_near(planet.orbit_point(FRAC_PI_2), Vec3::new(0.0, 0.0, 10.0));
+    }
+
+    // Un cometa retrógrado y un cuerpo tipo Plutón con el periastro en el lado sur (ω entre
+    // 180° y 360°) pasan por el perihelio con Y positiva; con ω en el lado norte, con Y negativa
+    #[test]
+    fn perihelion_side_follows_the_argument_of_periapsis() {
+        let body = |radius: f32, eccentricity: f32, inclination: f32, periapsis: f32, node: f32| {
+            let mut planet = Planet::new("Prueba", radius, 0.0, 1.0, Material::new("icy").unwrap());
+            planet.eccentricity = eccentricity;
+            planet.inclination = inclination;
+            planet.periapsis = periapsis;
+            planet.ascending_node = node;
+            planet
+        };
+        let comet = body(78.0, 0.8, 162.0, 292.0, 239.0);
+        let dwarf = body(120.0, 0.25, 17.0, 293.0, 290.0);
+        assert!(comet.orbit_point(0.0).y > 0.0);
+        assert!(dwarf.orbit_point(0.0).y > 0.0);
+
+        let comet = body(78.0, 0.8, 162.0, 112.0, 239.0);
+        let dwarf = body(120.0, 0.25, 17.0, 113.0, 290.0);
+        assert!(comet.orbit_point(0.0).y < 0.0);
+        assert!(dwarf.orbit_point(0.0).y < 0.0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+const HISTORY_FRAMES: usize = 60;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    Update,
+    Clear,
+    Vertex,
+    Sort,
+    Raster,
+    Shade,
+    Post,
+    Present,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 8] = [
+        Stage::Update,
+        Stage::Clear,
+        Stage::Vertex,
+        Stage::Sort,
+        Stage::Raster,
+        Stage::Shade,
+        Stage::Post,
+        Stage::Present,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Update => "update",
+            Stage::Clear => "clear",
+            Stage::Vertex => "vertex",
+            Stage::Sort => "sort",
+            Stage::Raster => "raster",
+            Stage::Shade => "shade",
+            Stage::Post => "post",
+            Stage::Present => "present",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameProfile {
+    pub stages: [Duration; Stage::ALL.len()],
+    pub triangles: usize,
+    pub fragments: usize,
+    pub covered_pixels: usize,
+}
+
+impl FrameProfile {
+    pub fn stage_ms(&self, stage: Stage) -> f32 {
+        self.stages[stage as usize].as_secs_f32() * 1000.0
+    }
+
+    pub fn total_ms(&self) -> f32 {
+        self.stages.iter().map(|stage| stage.as_secs_f32() * 1000.0).sum()
+    }
+
+    // Fragmentos sombreados por cada píxel que terminó cubierto
+    pub fn overdraw(&self) -> f32 {
+        if self.covered_pixels == 0 {
+            0.0
+        } else {
+            self.fragments as f32 / self.covered_pixels as f32
+        }
+    }
+}
+
+pub struct ScopedTimer<'a> {
+    profiler: &'a Profiler,
+    stage: Stage,
+    start: Instant,
+}
+
+impl Drop for ScopedTimer<'_> {
+    fn drop(&mut self) {
+        self.profiler.add_time(self.stage, self.start.elapsed());
+    }
+}
+
+// Usa mutabilidad interior para poder medir etapas desde funciones que solo reciben referencias
+pub struct Profiler {
+    current: Cell<FrameProfile>,
+    history: RefCell<VecDeque<FrameProfile>>,
+    csv: RefCell<Option<BufWriter<File>>>,
+    frame: Cell<u64>,
+}
+
+impl Profiler {
+    pub fn new(csv_path: Option<&str>) -> io::Result<Self> {
+        let csv = match csv_path {
+            Some(path) => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                let stages: Vec<String> = Stage::ALL.iter().map(|stage| format!("{}_ms", stage.name())).collect();
+                writeln!(writer, "frame,{},total_ms,triangles,fragments,covered_pixels,overdraw", stages.join(","))?;
+                Some(writer)
+            }
+            None => None,
+        };
+
+        Ok(Profiler {
+            current: Cell::new(FrameProfile::default()),
+            history: RefCell::new(VecDeque::with_capacity(HISTORY_FRAMES)),
+            csv: RefCell::new(csv),
+            frame: Cell::new(0),
+        })
+    }
+
+    pub fn scope(&self, stage: Stage) -> ScopedTimer<'_> {
+        ScopedTimer {
+            profiler: self,
+            stage,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn add_time(&self, stage: Stage, duration: Duration) {
+        let mut current = self.current.get();
+        current.stages[stage as usize] += duration;
+        self.current.set(current);
+    }
+
+    pub fn count(&self, triangles: usize, fragments: usize) {
+        let mut current = self.current.get();
+        current.triangles += triangles;
+        current.fragments += fragments;
+        self.current.set(current);
+    }
+
+    pub fn set_covered_pixels(&self, covered_pixels: usize) {
+        let mut current = self.current.get();
+        current.covered_pixels = covered_pixels;
+        self.current.set(current);
+    }
+
+    pub fn end_frame(&self) -> io::Result<()> {
+        let finished = self.current.replace(FrameProfile::default());
+        let frame = self.frame.get();
+        self.frame.set(frame + 1);
+
+        let mut history = self.history.borrow_mut();
+        if history.len() == HISTORY_FRAMES {
+            history.pop_front();
+        }
+        history.push_back(finished);
+
+        if let Some(writer) = self.csv.borrow_mut().as_mut() {
+            let stages: Vec<String> = Stage::ALL.iter().map(|stage| format!("{:.3}", finished.stage_ms(*stage))).collect();
+            writeln!(
+                writer,
+                "{},{},{:.3},{},{},{},{:.3}",
+                frame,
+                stages.join(","),
+                finished.total_ms(),
+                finished.triangles,
+                finished.fragments,
+                finished.covered_pixels,
+                finished.overdraw()
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        match self.csv.borrow_mut().as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    // Promedio de los últimos frames registrados
+    pub fn average(&self) -> FrameProfile {
+        let history = self.history.borrow();
+        let mut average = FrameProfile::default();
+        if history.is_empty() {
+            return average;
+        }
+
+        let frames = history.len();
+        for profile in history.iter() {
+            for (total, stage) in average.stages.iter_mut().zip(&profile.stages) {
+                *total += *stage;
+            }
+            average.triangles += profile.triangles;
+            average.fragments += profile.fragments;
+            average.covered_pixels += profile.covered_pixels;
+        }
+        for total in average.stages.iter_mut() {
+            *total /= frames as u32;
+        }
+        average.triangles /= frames;
+        average.fragments /= frames;
+        average.covered_pixels /= frames;
+        average
+    }
+}
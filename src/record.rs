@@ -0,0 +1,77 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::camera::Camera;
+use crate::clock::SimClock;
+use crate::config::Config;
+use crate::framebuffer::Framebuffer;
+use crate::orbit::OrbitRenderer;
+use crate::scene::Scene;
+use crate::RenderContext;
+
+pub enum RecordOutput {
+    Images(PathBuf),
+    Stdout,
+}
+
+pub struct RecordSettings {
+    pub frames: u32,
+    pub step: f32,
+    pub width: usize,
+    pub height: usize,
+    pub output: RecordOutput,
+}
+
+pub fn record(
+    settings: &RecordSettings,
+    config: &Config,
+    camera: &Camera,
+    scene: &mut Scene,
+    context: &RenderContext,
+) -> io::Result<()> {
+    let (render_width, render_height) = config.framebuffer_size(settings.width, settings.height);
+    let mut framebuffer = Framebuffer::with_samples(render_width, render_height, config.msaa);
+    framebuffer.set_background_color(0x000000);
+    let mut output = Framebuffer::new(settings.width, settings.height);
+
+    if let RecordOutput::Images(dir) = &settings.output {
+        fs::create_dir_all(dir)?;
+    }
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    let mut clock = SimClock::new();
+    let mut orbits = OrbitRenderer::new();
+    for frame in 0..settings.frames {
+        let shader_time = clock.shader_time();
+        let delta = clock.tick(settings.step);
+        scene.update(delta);
+        orbits.record(&scene.planets);
+        crate::render_scene(&mut framebuffer, camera, scene, shader_time, None, &orbits, context);
+
+        framebuffer.resolve_into(&mut output.buffer, output.width, output.height);
+        let rgb = output.to_rgb_bytes();
+        match &settings.output {
+            RecordOutput::Images(dir) => {
+                let path = dir.join(format!("frame_{:05}.ppm", frame));
+                write_ppm(&path, output.width, output.height, &rgb)?;
+            }
+            RecordOutput::Stdout => stdout.write_all(&rgb)?,
+        }
+
+        context.profiler.end_frame()?;
+        eprint!("\rGrabando frame {}/{}", frame + 1, settings.frames);
+    }
+    stdout.flush()?;
+    context.profiler.flush()?;
+    eprintln!();
+
+    Ok(())
+}
+
+fn write_ppm(path: &Path, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+    file.write_all(rgb)?;
+    file.flush()
+}
@@ -0,0 +1,465 @@
+use std::fmt;
+use std::fs;
+use std::sync::Arc;
+use nalgebra_glm::Vec3;
+use crate::asteroids::AsteroidBelt;
+use crate::ephemeris::Ephemeris;
+use crate::material::Material;
+use crate::mesh::NormalMode;
+use crate::model::Model;
+use crate::particles::{ParticleEmitter, ParticleKind};
+use crate::planet::{self, Planet, Rings};
+
+pub struct Scene {
+    pub planets: Vec<Planet>,
+    pub belts: Vec<AsteroidBelt>,
+    // Con efemérides, los planetas siguen la fecha en lugar de sus órbitas de la escena
+    pub ephemeris: Option<Ephemeris>,
+}
+
+struct BodyEntry {
+    name: String,
+    line: usize,
+    orbit_radius: f32,
+    orbit_speed: f32,
+    eccentricity: Option<f32>,
+    inclination: Option<f32>,
+    periapsis: Option<f32>,
+    ascending_node: Option<f32>,
+    size: f32,
+    shader: Option<String>,
+    vertex_shader: Option<String>,
+    model: Option<String>,
+    normals: Option<NormalMode>,
+    parent: Option<String>,
+    rings: Option<(f32, f32)>,
+    particles: Option<ParticleKind>,
+    activity_distance: Option<f32>,
+    asteroids: Option<usize>,
+    belt_width: f32,
+    seed: u64,
+    params: Vec<(usize, String, String)>,
+}
+
+impl BodyEntry {
+    fn new(name: &str, line: usize) -> Self {
+        BodyEntry {
+            name: name.to_string(),
+            line,
+            orbit_radius: 0.0,
+            orbit_speed: 0.0,
+            eccentricity: None,
+            inclination: None,
+            periapsis: None,
+            ascending_node: None,
+            size: 1.0,
+            shader: None,
+            vertex_shader: None,
+            model: None,
+            normals: None,
+            parent: None,
+            rings: None,
+            particles: None,
+            activity_distance: None,
+            asteroids: None,
+            belt_width: 0.0,
+            seed: 0,
+            params: Vec::new(),
+        }
+    }
+
+    fn material(&self) -> Result<Material, String> {
+        let shader = self
+            .shader
+            .as_ref()
+            .ok_or(format!("línea {}: el cuerpo {} no tiene shader", self.line, self.name))?;
+        let mut material = Material::new(shader).map_err(|err| format!("línea {}: {}", self.line, err))?;
+        if let Some(vertex_shader) = &self.vertex_shader {
+            material
+                .set_vertex_shader(vertex_shader)
+                .map_err(|err| format!("línea {}: {}", self.line, err))?;
+        }
+        for (line, key, value) in &self.params {
+            material
+                .set_param(key, value)
+                .map_err(|err| format!("línea {}: {}", line, err))?;
+        }
+        Ok(material)
+    }
+
+    // Los cuerpos ya construidos permiten resolver el padre por nombre
+    fn build(self, planets: &[Planet]) -> Result<Planet, String> {
+        let material = self.material()?;
+        let mut planet = Planet::new(&self.name, self.orbit_radius, self.orbit_speed, self.size, material);
+        planet.eccentricity = self.eccentricity.unwrap_or(0.0);
+        planet.inclination = self.inclination.unwrap_or(0.0);
+        planet.periapsis = self.periapsis.unwrap_or(0.0);
+        planet.ascending_node = self.ascending_node.unwrap_or(0.0);
+        if let Some(parent) = &self.parent {
+            planet.parent = Some(planets.iter().position(|planet| &planet.name == parent).ok_or(format!(
+                "línea {}: cuerpo padre desconocido: {} (debe definirse antes que {})",
+                self.line, parent, self.name
+            ))?);
+        }
+        if let Some((inner, outer)) = self.rings {
+            planet.rings = Some(Rings::new(inner, outer));
+        }
+        if let Some(kind) = self.particles {
+            planet.emitter = Some(ParticleEmitter::new(kind, planets.len() as u64));
+        }
+        if let Some(distance) = self.activity_distance {
+            match planet.emitter.as_mut() {
+                Some(emitter) if emitter.kind == ParticleKind::Tail => emitter.activity_distance = distance,
+                _ => {
+                    return Err(format!(
+                        "línea {}: el cuerpo {} usa activity_distance sin particles = tail",
+                        self.line, self.name
+                    ))
+                }
+            }
+        }
+        if let Some(path) = &self.model {
+            let mut model = Model::load(path).map_err(|err| format!("línea {}: {}", self.line, err))?;
+            if let Some(mode) = self.normals {
+                model.recompute_normals(mode);
+            }
+            planet.model = Some(Arc::new(model));
+        }
+        Ok(planet)
+    }
+
+    // Una sección con `asteroids` es un cinturón; el resto son cuerpos
+    fn build_into(self, planets: &mut Vec<Planet>, belts: &mut Vec<AsteroidBelt>) -> Result<(), String> {
+        match self.asteroids {
+            Some(count) => belts.push(self.build_belt(count)?),
+            None => planets.push(self.build(planets)?),
+        }
+        Ok(())
+    }
+
+    fn build_belt(self, count: usize) -> Result<AsteroidBelt, String> {
+        // El cinturón reparte sus rocas en órbitas circulares sobre el plano de referencia
+        let unsupported = [
+            ("model", self.model.is_some()),
+            ("parent", self.parent.is_some()),
+            ("rings", self.rings.is_some()),
+            ("particles", self.particles.is_some()),
+            ("eccentricity", self.eccentricity.is_some()),
+            ("inclination", self.inclination.is_some()),
+            ("periapsis", self.periapsis.is_some()),
+            ("ascending_node", self.ascending_node.is_some()),
+            ("activity_distance", self.activity_distance.is_some()),
+            ("normals", self.normals.is_some()),
+        ];
+        if let Some((key, _)) = unsupported.iter().find(|(_, present)| *present) {
+            return Err(format!("línea {}: el cinturón {} no admite {}", self.line, self.name, key));
+        }
+        if !(0.0..self.orbit_radius.abs() * 2.0).contains(&self.belt_width) {
+            return Err(format!(
+                "línea {}: belt_width debe estar entre 0 y el doble de orbit_radius (sin incluirlo)",
+                self.line
+            ));
+        }
+        let material = self.material()?;
+        Ok(AsteroidBelt::new(&self.name, self.orbit_radius, self.orbit_speed, self.size, material)
+            .with_asteroids(count, self.belt_width, self.seed))
+    }
+}
+
+impl Scene {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path, err))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut planets = Vec::new();
+        let mut belts = Vec::new();
+        let mut current: Option<BodyEntry> = None;
+
+        for (number, line) in contents.lines().enumerate() {
+            let number = number + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                if let Some(body) = current.take() {
+                    body.build_into(&mut planets, &mut belts)?;
+                }
+                current = Some(BodyEntry::new(name.trim(), number));
+                continue;
+            }
+
+            let body = current
+                .as_mut()
+                .ok_or(format!("línea {}: propiedad fuera de un cuerpo", number))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(format!("línea {}: se esperaba clave = valor", number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let parse_f32 = |value: &str| {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("línea {}: número inválido para {}: {}", number, key, value))
+            };
+
+            match key {
+                "orbit_radius" => body.orbit_radius = parse_f32(value)?,
+                "orbit_speed" => body.orbit_speed = parse_f32(value)?,
+                "eccentricity" => {
+                    let eccentricity = parse_f32(value)?;
+                    if !(0.0..1.0).contains(&eccentricity) {
+                        return Err(format!("línea {}: eccentricity debe estar entre 0 y 1 (sin incluir el 1)", number));
+                    }
+                    body.eccentricity = Some(eccentricity);
+                }
+                "inclination" => body.inclination = Some(parse_f32(value)?),
+                "periapsis" => body.periapsis = Some(parse_f32(value)?),
+                "ascending_node" => body.ascending_node = Some(parse_f32(value)?),
+                "size" => body.size = parse_f32(value)?,
+                "shader" => body.shader = Some(value.to_string()),
+                "vertex_shader" => body.vertex_shader = Some(value.to_string()),
+                "model" => body.model = Some(value.to_string()),
+                "parent" => body.parent = Some(value.to_string()),
+                "particles" => {
+                    body.particles = Some(ParticleKind::parse(value).ok_or(format!(
+                        "línea {}: valor inválido para particles: {} (usa tail o flares)",
+                        number, value
+                    ))?);
+                }
+                "activity_distance" => {
+                    let distance = parse_f32(value)?;
+                    if distance <= 0.0 {
+                        return Err(format!("línea {}: activity_distance debe ser positivo", number));
+                    }
+                    body.activity_distance = Some(distance);
+                }
+                "asteroids" => {
+                    body.asteroids = Some(value.parse::<usize>().map_err(|_| {
+                        format!("línea {}: número inválido para {}: {}", number, key, value)
+                    })?);
+                }
+                "belt_width" => body.belt_width = parse_f32(value)?,
+                "seed" => {
+                    body.seed = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("línea {}: número inválido para {}: {}", number, key, value))?;
+                }
+                "rings" => {
+                    let (inner, outer) = value
+                        .split_once(',')
+                        .ok_or(format!("línea {}: se esperaba rings = interior, exterior", number))?;
+                    let (inner, outer) = (parse_f32(inner.trim())?, parse_f32(outer.trim())?);
+                    if !(0.0 < inner && inner < outer) {
+                        return Err(format!("línea {}: el radio interior de rings debe ser positivo y menor que el exterior", number));
+                    }
+                    body.rings = Some((inner, outer));
+                }
+                "normals" => {
+                    body.normals = Some(match value {
+                        "smooth" => NormalMode::Smooth,
+                        "flat" => NormalMode::Flat,
+                        _ => return Err(format!("línea {}: valor inválido para normals: {} (usa smooth o flat)", number, value)),
+                    });
+                }
+                _ => body.params.push((number, key.to_string(), value.to_string())),
+            }
+        }
+
+        if let Some(body) = current.take() {
+            body.build_into(&mut planets, &mut belts)?;
+        }
+
+        Ok(Scene { planets, belts, ephemeris: None })
+    }
+
+    // Sustituye los cuerpos por los recargados conservando la posición orbital de los que ya existían
+    pub fn apply_reload(&mut self, mut reloaded: Scene) {
+        for planet in reloaded.planets.iter_mut() {
+            if let Some(previous) = self.planets.iter_mut().find(|previous| previous.name == planet.name) {
+                planet.current_angle = previous.current_angle;
+                planet.position = previous.position;
+                // Las partículas ya emitidas siguen vivas si el cuerpo conserva el mismo tipo de emisor;
+                // los ajustes (como activity_distance) son los del archivo recargado
+                if let (Some(emitter), Some(old)) = (planet.emitter.as_mut(), previous.emitter.take()) {
+                    if emitter.kind == old.kind {
+                        emitter.continue_from(old);
+                    }
+                }
+            }
+        }
+        // Un cinturón con las mismas rocas conserva también sus posiciones y giros
+        for belt in reloaded.belts.iter_mut() {
+            let previous = self.belts.iter().find(|previous| {
+                previous.name == belt.name && previous.asteroids.len() == belt.asteroids.len()
+            });
+            if let Some(previous) = previous {
+                for (asteroid, old) in belt.asteroids.iter_mut().zip(&previous.asteroids) {
+                    asteroid.current_angle = old.current_angle;
+                    asteroid.spin_angle = old.spin_angle;
+                    asteroid.update_position(0.0);
+                }
+            }
+        }
+        self.planets = reloaded.planets;
+        self.belts = reloaded.belts;
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some(ephemeris) = self.ephemeris.as_mut() {
+            ephemeris.apply(&mut self.planets);
+            ephemeris.advance(delta_time);
+        }
+        planet::update_positions(&mut self.planets, delta_time);
+        let sun = self.light_position();
+        for planet in self.planets.iter_mut() {
+            let radius = planet.size * crate::SPHERE_RADIUS;
+            if let Some(emitter) = planet.emitter.as_mut() {
+                emitter.update(delta_time, planet.position, radius, sun);
+            }
+        }
+        for belt in self.belts.iter_mut() {
+            belt.update(delta_time);
+        }
+    }
+
+    // La luz sale de la primera estrella (shader sun); sin estrella queda en el origen
+    pub fn light_position(&self) -> Vec3 {
+        self.planets
+            .iter()
+            .find(|planet| planet.material.fragment_shader.name() == "sun")
+            .map_or(Vec3::zeros(), |planet| planet.position)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|err| format!("No se pudo guardar {}: {}", path, err))
+    }
+}
+
+impl fmt::Display for Scene {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, planet) in self.planets.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "[{}]", planet.name)?;
+            if let Some(parent) = planet.parent.and_then(|parent| self.planets.get(parent)) {
+                writeln!(f, "parent = {}", parent.name)?;
+            }
+            writeln!(f, "orbit_radius = {}", planet.orbit_radius)?;
+            writeln!(f, "orbit_speed = {}", planet.orbit_speed)?;
+            // Los elementos de la órbita solo se escriben si no es la circular sobre el plano
+            if planet.eccentricity != 0.0 {
+                writeln!(f, "eccentricity = {}", planet.eccentricity)?;
+            }
+            if planet.inclination != 0.0 || planet.periapsis != 0.0 || planet.ascending_node != 0.0 {
+                writeln!(f, "inclination = {}", planet.inclination)?;
+                writeln!(f, "periapsis = {}", planet.periapsis)?;
+                writeln!(f, "ascending_node = {}", planet.ascending_node)?;
+            }
+            writeln!(f, "size = {}", planet.size)?;
+            if let Some(rings) = &planet.rings {
+                writeln!(f, "rings = {}, {}", rings.inner, rings.outer)?;
+            }
+            if let Some(emitter) = &planet.emitter {
+                writeln!(f, "particles = {}", emitter.kind.name())?;
+                if emitter.kind == ParticleKind::Tail {
+                    writeln!(f, "activity_distance = {}", emitter.activity_distance)?;
+                }
+            }
+            writeln!(f, "shader = {}", planet.material.fragment_shader.name())?;
+            writeln!(f, "vertex_shader = {}", planet.material.vertex_shader.name())?;
+            if let Some(model) = &planet.model {
+                writeln!(f, "model = {}", model.source)?;
+                match model.normals {
+                    Some(NormalMode::Smooth) => writeln!(f, "normals = smooth")?,
+                    Some(NormalMode::Flat) => writeln!(f, "normals = flat")?,
+                    None => {}
+                }
+            }
+            for (name, value) in planet.material.params() {
+                writeln!(f, "{} = {}", name, value)?;
+            }
+        }
+        for (index, belt) in self.belts.iter().enumerate() {
+            if index > 0 || !self.planets.is_empty() {
+                writeln!(f)?;
+            }
+            writeln!(f, "[{}]", belt.name)?;
+            writeln!(f, "orbit_radius = {}", belt.orbit_radius)?;
+            writeln!(f, "orbit_speed = {}", belt.orbit_speed)?;
+            writeln!(f, "size = {}", belt.size)?;
+            writeln!(f, "asteroids = {}", belt.asteroids.len())?;
+            writeln!(f, "belt_width = {}", belt.width)?;
+            writeln!(f, "seed = {}", belt.seed)?;
+            writeln!(f, "shader = {}", belt.material.fragment_shader.name())?;
+            writeln!(f, "vertex_shader = {}", belt.material.vertex_shader.name())?;
+            for (name, value) in belt.material.params() {
+                writeln!(f, "{} = {}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comet(activity_distance: f32) -> Scene {
+        Scene::parse(&format!(
+            "[Cometa]\norbit_radius = 60\norbit_speed = 0.001\nsize = 0.1\nshader = icy\neccentricity = 0.7\nparticles = tail\nactivity_distance = {}\n",
+            activity_distance
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn reload_keeps_emitter_with_new_settings() {
+        let mut scene = comet(40.0);
+        for _ in 0..50 {
+            scene.update(1.0);
+        }
+        let angle = scene.planets[0].current_angle;
+
+        scene.apply_reload(comet(15.0));
+        let emitter = scene.planets[0].emitter.as_ref().unwrap();
+        assert_eq!(emitter.activity_distance, 15.0);
+        assert_eq!(scene.planets[0].current_angle, angle);
+    }
+
+    #[test]
+    fn displacement_octaves_must_be_in_range() {
+        let body = |octaves: &str| {
+            Scene::parse(&format!(
+                "[Roca]\norbit_radius = 10\norbit_speed = 0.01\nsize = 1\nshader = icy\nvertex_shader = terrain\ndisplacement_octaves = {}\n",
+                octaves
+            ))
+        };
+        assert!(body("8").is_ok());
+        for octaves in ["0", "13", "1000000"] {
+            let err = body(octaves).err().unwrap();
+            assert!(err.contains("entre 1 y 12"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn belts_reject_orbit_and_body_keys() {
+        let belt = |extra: &str| {
+            Scene::parse(&format!(
+                "[Cinturón]\norbit_radius = 30\norbit_speed = 0.01\nsize = 0.1\nshader = icy\nasteroids = 10\nbelt_width = 4\n{}\n",
+                extra
+            ))
+        };
+        assert!(belt("").is_ok());
+        for key in ["eccentricity = 0.2", "inclination = 5", "periapsis = 10", "ascending_node = 20", "activity_distance = 5", "normals = flat"] {
+            let err = belt(key).err().unwrap();
+            let name = key.split(' ').next().unwrap();
+            assert_eq!(err, format!("línea 1: el cinturón Cinturón no admite {}", name));
+        }
+    }
+}
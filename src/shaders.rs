@@ -1,30 +1,178 @@
+use std::fmt;
 use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
-use fastnoise_lite::FastNoiseLite;
+use crate::bump;
+use crate::noise::{self, Fractal, FractalMode};
+use crate::lighting::{self, SurfacePoint};
 
 
+#[derive(Debug, Clone, Copy)]
+pub enum ShaderParam {
+  Float(f32),
+  Color(Color),
+}
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
-  let mut position = Vec4::new(
-      vertex.position.x,
-      vertex.position.y,
-      vertex.position.z,
-      1.0,
-  );
+impl ShaderParam {
+  // Interpreta el texto con el mismo tipo que el valor actual del parámetro
+  pub fn parse_like(&self, value: &str) -> Result<ShaderParam, String> {
+    match self {
+      ShaderParam::Float(_) => value
+          .trim()
+          .parse()
+          .map(ShaderParam::Float)
+          .map_err(|_| format!("Número inválido: {}", value)),
+      ShaderParam::Color(_) => Color::parse(value).map(ShaderParam::Color),
+    }
+  }
+}
+
+impl fmt::Display for ShaderParam {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ShaderParam::Float(value) => write!(f, "{}", value),
+      ShaderParam::Color(color) => write!(f, "#{:06X}", color.to_hex()),
+    }
+  }
+}
+
+pub trait ParamValue: Sized {
+  fn to_param(&self) -> ShaderParam;
+  fn from_param(param: ShaderParam) -> Option<Self>;
+}
+
+impl ParamValue for f32 {
+  fn to_param(&self) -> ShaderParam {
+    ShaderParam::Float(*self)
+  }
+
+  fn from_param(param: ShaderParam) -> Option<Self> {
+    match param {
+      ShaderParam::Float(value) => Some(value),
+      _ => None,
+    }
+  }
+}
+
+impl ParamValue for Color {
+  fn to_param(&self) -> ShaderParam {
+    ShaderParam::Color(*self)
+  }
+
+  fn from_param(param: ShaderParam) -> Option<Self> {
+    match param {
+      ShaderParam::Color(color) => Some(color),
+      _ => None,
+    }
+  }
+}
+
+pub trait ShaderParameters {
+  fn params(&self) -> Vec<(&'static str, ShaderParam)>;
+  fn set_param(&mut self, name: &str, value: ShaderParam) -> Result<(), String>;
+
+  fn set_param_str(&mut self, name: &str, value: &str) -> Result<(), String> {
+    let current = self
+        .params()
+        .into_iter()
+        .find(|(param, _)| *param == name)
+        .map(|(_, value)| value)
+        .ok_or(format!("Parámetro desconocido: {}", name))?;
+    self.set_param(name, current.parse_like(value)?)
+  }
+}
+
+pub trait VertexShader: ShaderParameters + Send + Sync {
+  fn name(&self) -> &'static str;
+  fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex;
+}
+
+pub trait FragmentShader: ShaderParameters + Send + Sync {
+  fn name(&self) -> &'static str;
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color;
+}
+
+// Genera el struct de un shader con sus parámetros nombrados y valores por defecto;
+// tras `;` se puede dar el rango admitido de un parámetro
+macro_rules! shader_params {
+  ($shader:ident { $($field:ident: $ty:ty = $default:expr $(; $range:expr)?,)* }) => {
+    pub struct $shader {
+      $(pub $field: $ty,)*
+    }
+
+    impl Default for $shader {
+      fn default() -> Self {
+        $shader {
+          $($field: $default,)*
+        }
+      }
+    }
+
+    impl ShaderParameters for $shader {
+      fn params(&self) -> Vec<(&'static str, ShaderParam)> {
+        vec![$((stringify!($field), self.$field.to_param()),)*]
+      }
+
+      // Los shaders sin parámetros no usan `value`
+      #[allow(unused_variables)]
+      fn set_param(&mut self, name: &str, value: ShaderParam) -> Result<(), String> {
+        match name {
+          $(stringify!($field) => {
+            let value = <$ty>::from_param(value)
+                .ok_or(format!("Tipo inválido para {}", name))?;
+            $(
+              let range = $range;
+              if !range.contains(&value) {
+                return Err(format!("{} debe estar entre {} y {}", name, range.start(), range.end()));
+              }
+            )?
+            self.$field = value;
+            Ok(())
+          })*
+          _ => Err(format!("Parámetro desconocido: {}", name)),
+        }
+      }
+    }
+  };
+}
+
+pub fn vertex_shader_by_name(name: &str) -> Option<Box<dyn VertexShader>> {
+  match name {
+    "standard" => Some(Box::new(StandardVertexShader::default())),
+    "wobble" => Some(Box::new(WobbleVertexShader::default())),
+    "terrain" => Some(Box::new(TerrainVertexShader::default())),
+    _ => None,
+  }
+}
+
+pub fn fragment_shader_by_name(name: &str) -> Option<Box<dyn FragmentShader>> {
+  match name {
+    "sun" => Some(Box::new(SunShader::default())),
+    "earth" => Some(Box::new(EarthShader::default())),
+    "mars" => Some(Box::new(MarsShader::default())),
+    "jupiter" => Some(Box::new(JupiterShader::default())),
+    "saturn" => Some(Box::new(SaturnShader::default())),
+    "icy" => Some(Box::new(IcyShader::default())),
+    "surface" => Some(Box::new(SurfaceShader::default())),
+    _ => None,
+  }
+}
 
-  let wobble = (uniforms.time as f32 * 0.02).sin() * 0.05;
-  position.x += wobble * vertex.position.y;
-  position.y += wobble * vertex.position.z;
 
+// w mínima para proyectar un vértice
+const W_EPSILON: f32 = 1e-5;
+
+fn transform_vertex(vertex: &Vertex, position: Vec4, uniforms: &Uniforms) -> Vertex {
   let transformed = uniforms.projection_matrix
       * uniforms.view_matrix
       * uniforms.model_matrix
       * position;
 
-  let w = transformed.w;
+  // Un vértice en el plano de la cámara o detrás no tiene proyección: queda en NaN y el
+  // rasterizador descarta sus triángulos
+  let w = if transformed.w > W_EPSILON { transformed.w } else { f32::NAN };
   let transformed_position = Vec4::new(
       transformed.x / w,
       transformed.y / w,
@@ -37,11 +185,14 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
   let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
   let transformed_normal = normal_matrix * vertex.normal;
+  // La tangente vive en el plano de la superficie, así que se transforma con la matriz del modelo
+  let tangent = model_mat3 * vertex.tangent.xyz();
 
   Vertex {
       position: vertex.position,
       normal: vertex.normal,
       tex_coords: vertex.tex_coords,
+      tangent: vertex.tangent,
       color: vertex.color,
       transformed_position: Vec3::new(
           screen_position.x,
@@ -49,282 +200,529 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
           screen_position.z,
       ),
       transformed_normal,
+      transformed_tangent: Vec4::new(tangent.x, tangent.y, tangent.z, vertex.tangent.w),
   }
 }
 
+shader_params!(StandardVertexShader {});
 
-pub fn sun_shader(uniforms: &Uniforms) -> Color {
-  let pulsate = ((uniforms.time as f32 * 0.01).sin() + 1.0) / 2.0;
-
-  let surface_noise = uniforms.noise.get_noise_2d(
-      uniforms.time as f32 * 0.1,
-      uniforms.time as f32 * 0.1,
-  );
+impl VertexShader for StandardVertexShader {
+  fn name(&self) -> &'static str {
+    "standard"
+  }
 
-  let eruption_noise = uniforms.noise.get_noise_2d(
-      uniforms.time as f32 * 0.02,
-      (uniforms.time as f32 * 0.02).cos(),
-  );
+  fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    let position = Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
+    transform_vertex(vertex, position, uniforms)
+  }
+}
 
-  let core_color = Color::new(255, 140, 0);    
-  let flare_color = Color::new(255, 69, 0);    
-  let corona_color = Color::new(255, 255, 160); 
+shader_params!(WobbleVertexShader {});
 
-  let core = core_color.lerp(&flare_color, surface_noise);
+impl VertexShader for WobbleVertexShader {
+  fn name(&self) -> &'static str {
+    "wobble"
+  }
 
-  let corona_intensity = (uniforms.time as f32 * 0.005).cos().abs();
-  let corona = corona_color * corona_intensity;
+  fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    let mut position = Vec4::new(
+        vertex.position.x,
+        vertex.position.y,
+        vertex.position.z,
+        1.0,
+    );
 
-  let flare_intensity = if eruption_noise > 0.8 {
-      1.5 
-  } else {
-      1.0 
-  };
+    let wobble = (uniforms.time as f32 * 0.02).sin() * 0.05;
+    position.x += wobble * vertex.position.y;
+    position.y += wobble * vertex.position.z;
 
-  let final_color = (core + corona) * pulsate * flare_intensity;
+    transform_vertex(vertex, position, uniforms)
+  }
+}
 
-  let halo_color = Color::new(255, 215, 0); 
-  let halo_intensity = ((uniforms.time as f32 * 0.002).sin().abs() * 0.5).clamp(0.0, 1.0);
+shader_params!(TerrainVertexShader {
+  displacement_height: f32 = 0.03,
+  displacement_scale: f32 = 4.0,
+  displacement_octaves: f32 = 5.0; 1.0..=12.0,
+  displacement_lacunarity: f32 = 2.0,
+  displacement_gain: f32 = 0.5,
+});
+
+// Relieve real: desplaza cada vértice a lo largo de su normal según un campo de altura fBm,
+// así las montañas se ven en la silueta. Necesita mallas con suficiente detalle (LOD alto)
+impl VertexShader for TerrainVertexShader {
+  fn name(&self) -> &'static str {
+    "terrain"
+  }
 
-  final_color + halo_color * halo_intensity
+  fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    let fractal = Fractal::fbm(self.displacement_octaves as usize)
+        .with_lacunarity(self.displacement_lacunarity)
+        .with_gain(self.displacement_gain);
+    let height = |position: &Vec3| {
+        fractal.sample_3d(uniforms.noise, &(noise::sphere_point(position) * self.displacement_scale))
+    };
+
+    let mut displaced = vertex.clone();
+    displaced.position = vertex.position + vertex.normal * (height(&vertex.position) * self.displacement_height);
+    displaced.normal = bump::perturb_normal(
+        &vertex.normal,
+        &vertex.tangent,
+        &vertex.position,
+        self.displacement_height,
+        0.01 / self.displacement_scale,
+        height,
+    );
+
+    let position = Vec4::new(displaced.position.x, displaced.position.y, displaced.position.z, 1.0);
+    transform_vertex(&displaced, position, uniforms)
+  }
 }
 
 
-pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let ocean_color = Color::new(0, 102, 204);  
-  let land_color = Color::new(34, 139, 34);   
-  let desert_color = Color::new(210, 180, 140);  
-  let mountain_color = Color::new(139, 137, 137);  
-  let cloud_color = Color::new(255, 255, 255);  
-  let ice_color = Color::new(240, 248, 255);  
+shader_params!(SunShader {
+  core_color: Color = Color::new(255, 140, 0),
+  flare_color: Color = Color::new(255, 69, 0),
+  corona_color: Color = Color::new(255, 255, 160),
+  halo_color: Color = Color::new(255, 215, 0),
+});
 
-  let t_clouds = uniforms.time as f32 * 0.02;
-  let t_surface = uniforms.time as f32 * 0.005;
+impl FragmentShader for SunShader {
+  fn name(&self) -> &'static str {
+    "sun"
+  }
 
-  let terrain_noise = fbm_noise(
-      &uniforms.noise,
-      fragment.vertex_position.x * 15.0,
-      fragment.vertex_position.y * 15.0,
-      6,  
-  );
+  fn shade(&self, _fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let pulsate = ((uniforms.time as f32 * 0.01).sin() + 1.0) / 2.0;
 
-  let biome_noise = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 50.0 + t_surface,
-      fragment.vertex_position.y * 50.0 + t_surface,
-  );
+    // El brillo de la superficie cambia despacio con el tiempo
+    let t = uniforms.time as f32 * 0.001;
+    let surface_noise = Fractal::fbm(3).sample_2d(uniforms.noise, t, t);
 
-  let cloud_noise = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 100.0 + t_clouds,
-      fragment.vertex_position.y * 100.0 + t_clouds,
-  );
+    let core = self.core_color.lerp(&self.flare_color, surface_noise);
 
-  let biome_color = if biome_noise > 0.7 {
-      mountain_color.lerp(&land_color, terrain_noise)  
-  } else if biome_noise > 0.5 {
-      desert_color * (1.0 - terrain_noise).clamp(0.5, 1.0) 
-  } else {
-      land_color * terrain_noise  
-  };
+    let corona_intensity = (uniforms.time as f32 * 0.005).cos().abs();
+    let corona = self.corona_color * corona_intensity;
 
-  let surface_color = if biome_noise < 0.3 {
-      ocean_color.lerp(&biome_color, biome_noise / 0.3)  
-  } else {
-      biome_color  
-  };
-
-  let final_color = if cloud_noise > 0.8 {
-      cloud_color  
-  } else if cloud_noise > 0.6 {
-      ice_color.lerp(&surface_color, 0.5)  
-  } else {
-      surface_color  
-  };
+    // Las protuberancias se dibujan con el sistema de partículas (particles = flares)
+    let final_color = (core + corona) * pulsate;
 
-  let light_factor = 0.6 + 0.4 * terrain_noise * fragment.vertex_position.z.clamp(-1.0, 1.0);
+    let halo_intensity = ((uniforms.time as f32 * 0.002).sin().abs() * 0.5).clamp(0.0, 1.0);
 
-  let illuminated_color = final_color * light_factor;
-  illuminated_color * fragment.intensity
+    final_color + self.halo_color * halo_intensity
+  }
 }
 
 
-pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let sand_color = Color::new(210, 77, 38);    
-  let rock_color = Color::new(150, 75, 45);    
-  let crater_color = Color::new(105, 54, 36);  
-  let ridge_color = Color::new(130, 60, 35);   
-  let t = uniforms.time as f32 * 0.3;
-  let spherical_x = fragment.vertex_position.x / fragment.vertex_position.z.abs().max(0.1);
-  let spherical_y = fragment.vertex_position.y / fragment.vertex_position.z.abs().max(0.1);
-  let fine_noise = fbm_noise(
-      &uniforms.noise,
-      spherical_x * 60.0 + t,
-      spherical_y * 60.0 + t,
-      5,
-  );
-
-  let crater_noise = uniforms.noise.get_noise_3d(
-      spherical_x * 40.0 + t * 0.5,
-      spherical_y * 40.0 + t * 0.5,
-      fragment.vertex_position.z * 40.0,
-  );
-
-  let rock_noise = fbm_noise(
-      &uniforms.noise,
-      spherical_x * 20.0,
-      spherical_y * 20.0,
-      6,
-  );
-
-  let rock_mask = uniforms.noise.get_noise_2d(
-      spherical_x * 25.0,
-      spherical_y * 25.0,
-  );
-  let small_rock_factor = (rock_mask * rock_noise).clamp(0.0, 1.0);
-
-  let surface_color = if crater_noise > 0.75 {
-      crater_color * (1.0 - crater_noise).clamp(0.5, 1.0) * fragment.intensity
-  } else if small_rock_factor > 0.6 {
-      rock_color * small_rock_factor * fragment.intensity
-  } else {
-      sand_color.lerp(&ridge_color, fine_noise) * fragment.intensity
-  };
-
-  let light_factor = 0.5 + 0.5 * fragment.vertex_position.z.clamp(-1.0, 1.0);
-  let final_color = surface_color * light_factor;
+shader_params!(EarthShader {
+  ocean_color: Color = Color::new(0, 102, 204),
+  land_color: Color = Color::new(34, 139, 34),
+  desert_color: Color = Color::new(210, 180, 140),
+  mountain_color: Color = Color::new(139, 137, 137),
+  cloud_color: Color = Color::new(255, 255, 255),
+  ice_color: Color = Color::new(240, 248, 255),
+  terrain_scale: f32 = 6.0,
+  biome_scale: f32 = 1.5,
+  cloud_scale: f32 = 2.5,
+  cloud_warp: f32 = 0.8,
+  mountain_threshold: f32 = 0.35,
+  desert_threshold: f32 = 0.2,
+  ocean_threshold: f32 = 0.05,
+  cloud_threshold: f32 = -0.1,
+  ice_threshold: f32 = 0.75,
+  bump_strength: f32 = 0.03,
+  ocean_roughness: f32 = 0.2,
+  land_roughness: f32 = 0.9,
+});
+
+impl FragmentShader for EarthShader {
+  fn name(&self) -> &'static str {
+    "earth"
+  }
 
-  final_color
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let t_clouds = uniforms.time as f32 * 0.0005;
+    let point = noise::sphere_point(&fragment.vertex_position);
+
+    let terrain_height = |position: &Vec3| {
+        TERRAIN.sample_3d(uniforms.noise, &(noise::sphere_point(position) * self.terrain_scale))
+    };
+    let terrain_noise = terrain_height(&fragment.vertex_position) * 0.5 + 0.5;
+
+    // Los continentes se deforman con domain warping para que las costas no sean manchas redondas
+    let continents = noise::warp(uniforms.noise, &(point * self.biome_scale), 0.5, &Fractal::fbm(3));
+    let biome_noise = Fractal::fbm(5).sample_3d(uniforms.noise, &continents);
+
+    // Las nubes se desplazan por el campo 3D, sin costuras al rodear el planeta, y cambian de forma
+    // con el tiempo como cuarta coordenada
+    let drift = Vec3::new(t_clouds, 0.0, t_clouds * 0.5);
+    let swirl = noise::warp(uniforms.noise, &(point * self.cloud_scale + drift), self.cloud_warp, &Fractal::fbm(3));
+    let cloud_noise = CLOUDS.sample_4d(uniforms.noise, &Vec4::new(swirl.x, swirl.y, swirl.z, t_clouds * 4.0));
+
+    let biome_color = if biome_noise > self.mountain_threshold {
+        self.mountain_color.lerp(&self.land_color, terrain_noise)
+    } else if biome_noise > self.desert_threshold {
+        self.desert_color * (1.0 - terrain_noise).clamp(0.5, 1.0)
+    } else {
+        self.land_color * (0.5 + terrain_noise)
+    };
+
+    // Fracción de agua: 1 en mar abierto y 0 tierra adentro, con una costa de ancho fijo
+    let water = ((self.ocean_threshold - biome_noise) / 0.05 + 0.5).clamp(0.0, 1.0);
+    let surface_color = biome_color.lerp(&self.ocean_color, water);
+
+    // Casquetes polares según la latitud, con bordes irregulares
+    let polar = point.y.abs() + terrain_noise * 0.15;
+    let (surface_color, water) = if polar > self.ice_threshold {
+        (self.ice_color, 0.0)
+    } else {
+        (surface_color, water)
+    };
+
+    // Las nubes tapan el reflejo del océano
+    let cloud_cover = ((cloud_noise - self.cloud_threshold) / 0.15).clamp(0.0, 1.0);
+    let final_color = surface_color.lerp(&self.cloud_color, cloud_cover);
+    let water = water * (1.0 - cloud_cover);
+
+    // El relieve solo se nota en tierra firme; el océano y las nubes conservan la normal lisa
+    let normal = if water > 0.5 || cloud_cover > 0.5 {
+        fragment.normal
+    } else {
+        bump::bump_normal(fragment, uniforms, self.bump_strength, 0.01 / self.terrain_scale, terrain_height)
+    };
+
+    let roughness = self.land_roughness + (self.ocean_roughness - self.land_roughness) * water;
+    let point = SurfacePoint::dielectric(final_color, roughness);
+    lighting::shade(
+        uniforms.lighting,
+        &point,
+        &normal,
+        &lighting::view_dir(fragment, uniforms),
+        &lighting::light_dir(fragment, uniforms),
+    )
+  }
 }
 
 
-
-fn fbm_noise(noise: &FastNoiseLite, x: f32, y: f32, octaves: usize) -> f32 {
-  let mut value = 0.0;
-  let mut amplitude = 1.0;
-  let  frequency = 1.0;
-
-  for i in 0..octaves {
- 
-      let offset = i as f32 * 0.1;
-      value += noise.get_noise_2d(x * frequency + offset, y * frequency + offset) * amplitude;
-      amplitude *= 0.6;  
+shader_params!(MarsShader {
+  sand_color: Color = Color::new(210, 77, 38),
+  rock_color: Color = Color::new(150, 75, 45),
+  crater_color: Color = Color::new(105, 54, 36),
+  ridge_color: Color = Color::new(130, 60, 35),
+  fine_scale: f32 = 12.0,
+  crater_scale: f32 = 5.0,
+  rock_scale: f32 = 4.0,
+  crater_radius: f32 = 0.3,
+  rock_threshold: f32 = 0.55,
+  bump_strength: f32 = 0.02,
+  roughness: f32 = 0.9,
+});
+
+impl FragmentShader for MarsShader {
+  fn name(&self) -> &'static str {
+    "mars"
   }
 
-  value
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let t = uniforms.time as f32 * 0.0005;
+    let point = noise::sphere_point(&fragment.vertex_position);
+
+    // Polvo que se desplaza lentamente sobre la superficie
+    let fine_noise = Fractal::fbm(5).sample_3d(uniforms.noise, &(point * self.fine_scale + Vec3::new(t, 0.0, t))) * 0.5 + 0.5;
+
+    // Los cráteres son los alrededores de los puntos de un ruido celular
+    let crater_depth = |point: &Vec3| crater_profile(noise::worley(uniforms.noise, &(point * self.crater_scale)).f1 / self.crater_radius);
+    let crater = crater_depth(&point);
+
+    // Crestas rocosas, solo donde la máscara lo permite
+    let rock_mask = Fractal::fbm(3).sample_3d(uniforms.noise, &(point * self.rock_scale * 0.5)) * 0.5 + 0.5;
+    let rocks = ROCKS.sample_3d(uniforms.noise, &(point * self.rock_scale)) * 0.5 + 0.5;
+    let small_rock_factor = rocks * rock_mask * 2.0;
+
+    // Las rocas sobresalen y los cráteres se hunden
+    let relief = |position: &Vec3| {
+        let point = noise::sphere_point(position);
+        (ROCKS.sample_3d(uniforms.noise, &(point * self.rock_scale)) * 0.5 + 0.5) * 0.5 + crater_depth(&point)
+    };
+    let normal = bump::bump_normal(fragment, uniforms, self.bump_strength, 0.01 / self.crater_scale, relief);
+
+    let albedo = if crater < 0.0 {
+        self.crater_color.lerp(&self.sand_color, 1.0 + crater)
+    } else if small_rock_factor > self.rock_threshold {
+        self.rock_color * small_rock_factor.min(1.0)
+    } else {
+        self.sand_color.lerp(&self.ridge_color, fine_noise)
+    };
+    let point = SurfacePoint::dielectric(albedo, self.roughness);
+    lighting::shade(
+        uniforms.lighting,
+        &point,
+        &normal,
+        &lighting::view_dir(fragment, uniforms),
+        &lighting::light_dir(fragment, uniforms),
+    )
+  }
 }
 
-
-pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-
-  let band_yellow = Color::new(255, 239, 170); 
-  let band_beige = Color::new(230, 220, 170);  
-  let band_brown = Color::new(180, 120, 70);   
-  let band_dark_brown = Color::new(120, 70, 40); 
-
-  let storm_color = Color::new(255, 69, 0);  
-
-  let t = uniforms.time as f32 * 0.02; 
-
-  let y_position = fragment.vertex_position.y * 15.0;
-
-  let wave_pattern = (y_position + (t * 2.0).sin()).sin(); 
-  let fbm_value = fbm_noise(
-      &uniforms.noise,
-      fragment.vertex_position.x * 1.5 + t * 0.05,
-      fragment.vertex_position.y * 3.0,
-      6,
-  );
-
-  let turbulence = uniforms.noise.get_noise_2d(
-      fragment.vertex_position.x * 10.0 + t * 0.3,
-      fragment.vertex_position.y * 10.0,
-  );
-
-  let wave_intensity = ((wave_pattern + fbm_value * 0.5) * 0.5 + 0.5) * (1.0 + turbulence * 0.2);
-
-  let band_color = band_yellow
-      .lerp(&band_beige, wave_intensity * 0.5) 
-      .lerp(&band_brown, wave_intensity * 0.8) 
-      .lerp(&band_dark_brown, wave_intensity); 
-
-  let red_spot_dist = ((fragment.vertex_position.x + 0.2).powi(2)
-      + (fragment.vertex_position.y - 0.2).powi(2))
-      .sqrt();
-  let red_spot_intensity = (1.0 - red_spot_dist * 4.0).clamp(0.0, 1.0);
-
-  let final_color = if red_spot_intensity > 0.7 {
-      storm_color * red_spot_intensity 
+// Perfil de un cráter según la distancia al centro en radios: cuenco de -1 en el centro
+// y un borde elevado que se desvanece a 1.25 radios
+fn crater_profile(distance: f32) -> f32 {
+  if distance < 1.0 {
+    distance * distance * 1.25 - 1.0
   } else {
-      band_color * fragment.intensity 
-  };
-
-  final_color * (1.0 + 0.15 * turbulence).clamp(0.0, 1.2)
+    (0.25 - (distance - 1.0)).max(0.0)
+  }
 }
 
 
+// Fractales compartidos por los shaders de planetas
+const TERRAIN: Fractal = Fractal::fbm(6);
+const CLOUDS: Fractal = Fractal::fbm(5).with_mode(FractalMode::Billow).with_gain(0.55);
+const ROCKS: Fractal = Fractal::ridged(6).with_lacunarity(2.2);
+const TURBULENCE: Fractal = Fractal::fbm(3).with_mode(FractalMode::Turbulence);
+
+
+shader_params!(JupiterShader {
+  band_yellow: Color = Color::new(255, 239, 170),
+  band_beige: Color = Color::new(230, 220, 170),
+  band_brown: Color = Color::new(180, 120, 70),
+  band_dark_brown: Color = Color::new(120, 70, 40),
+  storm_color: Color = Color::new(255, 69, 0),
+  band_frequency: f32 = 7.5,
+  band_warp: f32 = 0.3,
+  storm_longitude: f32 = -0.4,
+  storm_latitude: f32 = -0.35,
+  storm_falloff: f32 = 2.0,
+  roughness: f32 = 1.0,
+});
+
+impl FragmentShader for JupiterShader {
+  fn name(&self) -> &'static str {
+    "jupiter"
+  }
 
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let t = uniforms.time as f32 * 0.02;
+    let point = noise::sphere_point(&fragment.vertex_position);
+
+    // Las bandas siguen la latitud; el domain warping las ondula y forma remolinos en los bordes
+    let drift = Vec3::new(t * 0.01, 0.0, 0.0);
+    let swirl = noise::warp(uniforms.noise, &(point * 3.0 + drift), self.band_warp, &Fractal::fbm(4));
+    let y_position = swirl.y / 3.0 * self.band_frequency;
+
+    let wave_pattern = (y_position + (t * 2.0).sin()).sin();
+    let fbm_value = Fractal::fbm(6).sample_3d(uniforms.noise, &Vec3::new(swirl.x * 0.5, swirl.y * 2.0, swirl.z * 0.5));
+    let turbulence = TURBULENCE.sample_3d(uniforms.noise, &(point * 8.0 + drift * 3.0));
+
+    let wave_intensity = ((wave_pattern + fbm_value) * 0.5 + 0.5) * (1.0 + turbulence * 0.2);
+
+    let band_color = self.band_yellow
+        .lerp(&self.band_beige, wave_intensity * 0.5)
+        .lerp(&self.band_brown, wave_intensity * 0.8)
+        .lerp(&self.band_dark_brown, wave_intensity);
+
+    // La gran mancha roja se ubica por longitud y latitud y es más ancha que alta
+    let storm_center = Vec3::new(
+        self.storm_latitude.cos() * self.storm_longitude.sin(),
+        self.storm_latitude.sin(),
+        self.storm_latitude.cos() * self.storm_longitude.cos(),
+    );
+    let offset = point - storm_center;
+    let red_spot_dist = (offset.x * offset.x * 0.4 + offset.y * offset.y + offset.z * offset.z * 0.4).sqrt();
+    let red_spot_intensity = (1.0 - red_spot_dist * self.storm_falloff).clamp(0.0, 1.0);
+
+    let albedo = if red_spot_intensity > 0.7 {
+        self.storm_color * red_spot_intensity
+    } else {
+        band_color
+    };
+
+    // Las nubes de los gigantes gaseosos son mates: sin brillo especular apreciable
+    let lit = lighting::shade(
+        uniforms.lighting,
+        &SurfacePoint::dielectric(albedo, self.roughness),
+        &fragment.normal,
+        &lighting::view_dir(fragment, uniforms),
+        &lighting::light_dir(fragment, uniforms),
+    );
+    lit * (1.0 + 0.15 * turbulence).clamp(0.0, 1.2)
+  }
+}
 
 
-pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let band_color1 = Color::new(210, 180, 140);  
-  let band_color2 = Color::new(170, 140, 110);  
-  let band_color3 = Color::new(240, 230, 140);  
-  let band_color4 = Color::new(200, 160, 100);  
-
-  let ring_color1 = Color::new(192, 192, 192);  
-  let ring_color2 = Color::new(169, 169, 169);  
-  let ring_color3 = Color::new(220, 220, 220);  
-
-
-  let radius = (fragment.vertex_position.x.powi(2) + fragment.vertex_position.z.powi(2)).sqrt();
-  let angle = fragment.vertex_position.z.atan2(fragment.vertex_position.x);
-
-
-  let ring_noise = fbm_noise(
-      &uniforms.noise,
-      radius * 10.0,
-      angle * 5.0 + uniforms.time as f32 * 0.05,  
-      4, 
-  );
-
-  let wave_pattern = (radius * 6.0 + (uniforms.time as f32 * 0.1).sin()).sin();
+shader_params!(SaturnShader {
+  band_color1: Color = Color::new(210, 180, 140),
+  band_color2: Color = Color::new(170, 140, 110),
+  band_color3: Color = Color::new(240, 230, 140),
+  band_color4: Color = Color::new(200, 160, 100),
+  ring_color1: Color = Color::new(192, 192, 192),
+  ring_color2: Color = Color::new(169, 169, 169),
+  ring_color3: Color = Color::new(220, 220, 220),
+  ring_inner: f32 = 0.65,
+  ring_outer: f32 = 1.15,
+  band_scale: f32 = 4.0,
+  roughness: f32 = 1.0,
+});
+
+impl FragmentShader for SaturnShader {
+  fn name(&self) -> &'static str {
+    "saturn"
+  }
 
-  let ring_color = if ring_noise > 0.7 {
-      ring_color1.lerp(&ring_color2, wave_pattern * 0.5 + 0.5)
-  } else if ring_noise > 0.4 {
-      ring_color2.lerp(&ring_color3, wave_pattern * 0.5 + 0.5)
-  } else {
-      ring_color3 * (0.8 + 0.2 * ring_noise)  
-  };
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    // Distancia al centro: la esfera queda en 0.5 y la malla de anillos más afuera
+    let radius = fragment.vertex_position.norm();
+    let angle = fragment.vertex_position.z.atan2(fragment.vertex_position.x);
+
+
+    // El ángulo entra como punto de un círculo para que el patrón no se corte en ±π
+    let ring_noise = Fractal::fbm(4).sample_3d(
+        uniforms.noise,
+        &Vec3::new(radius * 4.0, angle.cos() * 2.0, angle.sin() * 2.0 + uniforms.time as f32 * 0.0005),
+    ) * 0.5 + 0.5;
+
+    let wave_pattern = (radius * 6.0 + (uniforms.time as f32 * 0.1).sin()).sin();
+
+    let ring_color = if ring_noise > 0.7 {
+        self.ring_color1.lerp(&self.ring_color2, wave_pattern * 0.5 + 0.5)
+    } else if ring_noise > 0.4 {
+        self.ring_color2.lerp(&self.ring_color3, wave_pattern * 0.5 + 0.5)
+    } else {
+        self.ring_color3 * (0.8 + 0.2 * ring_noise)
+    };
+
+
+    let pos = fragment.vertex_position.normalize();
+    let latitude = pos.y;
+
+    let band_noise = Fractal::fbm(6).sample_2d(
+        uniforms.noise,
+        latitude * self.band_scale + uniforms.time as f32 * 0.0001,
+        0.0,
+    ) * 0.5 + 0.5;
+
+    let band_color = if band_noise > 0.75 {
+        self.band_color1
+    } else if band_noise > 0.5 {
+        self.band_color2
+    } else if band_noise > 0.25 {
+        self.band_color3
+    } else {
+        self.band_color4
+    };
+
+    // Los anillos se desvanecen hacia el borde exterior
+    let ring_width = (self.ring_outer - self.ring_inner).max(f32::EPSILON);
+    let ring_opacity = ((self.ring_outer - radius) / ring_width * 3.0).clamp(0.1, 0.9);
+    let view = lighting::view_dir(fragment, uniforms);
+    let light = lighting::light_dir(fragment, uniforms);
+
+    if radius > self.ring_inner && radius < self.ring_outer {
+        let point = SurfacePoint::dielectric(ring_color, self.roughness);
+        lighting::shade(uniforms.lighting, &point, &fragment.normal, &view, &light) * ring_opacity
+    } else {
+        let point = SurfacePoint::dielectric(band_color, self.roughness);
+        lighting::shade(uniforms.lighting, &point, &fragment.normal, &view, &light)
+    }
+  }
+}
 
 
-  let pos = fragment.vertex_position.normalize();
-  let latitude = pos.y;
+shader_params!(IcyShader {
+  ice_color: Color = Color::new(225, 235, 245),
+  frost_color: Color = Color::new(180, 200, 220),
+  dust_color: Color = Color::new(110, 100, 90),
+  dark_color: Color = Color::new(45, 40, 38),
+  ice_coverage: f32 = 0.5,
+  ice_scale: f32 = 2.5,
+  dust_scale: f32 = 9.0,
+  crater_scale: f32 = 4.0,
+  crater_radius: f32 = 0.3,
+  bump_strength: f32 = 0.015,
+  ice_roughness: f32 = 0.35,
+  dust_roughness: f32 = 0.95,
+});
+
+// Cuerpos pequeños y helados: núcleos de cometa (polvo oscuro con poco hielo) y planetas enanos
+// (llanuras de hielo con manchas de polvo). ice_coverage es la fracción de la superficie con hielo
+impl FragmentShader for IcyShader {
+  fn name(&self) -> &'static str {
+    "icy"
+  }
 
-  let band_noise = fbm_noise(
-      &uniforms.noise,
-      latitude * 25.0 + uniforms.time as f32 * 0.01, 
-      0.0,
-      6,  
-  );
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let point = noise::sphere_point(&fragment.vertex_position);
+
+    // Manto de polvo con vetas de material orgánico más oscuro
+    let dust = Fractal::fbm(4).sample_3d(uniforms.noise, &(point * self.dust_scale)) * 0.5 + 0.5;
+    let dust_albedo = self.dust_color.lerp(&self.dark_color, dust);
+
+    // El hielo cubre las zonas donde el campo supera el umbral, con un borde de escarcha
+    let field = TERRAIN.sample_3d(uniforms.noise, &(point * self.ice_scale)) * 0.5 + 0.5;
+    let threshold = 1.0 - self.ice_coverage;
+    let ice = ((field - threshold) / 0.08).clamp(0.0, 1.0);
+    let frost = ((field - threshold + 0.08) / 0.08).clamp(0.0, 1.0) - ice;
+    let cracks = ROCKS.sample_3d(uniforms.noise, &(point * self.ice_scale * 3.0)) * 0.5 + 0.5;
+    let ice_albedo = self.ice_color.lerp(&self.frost_color, cracks * cracks);
+
+    // Los cráteres oscurecen el fondo, donde el hielo expuesto ya sublimó
+    let crater_depth = |point: &Vec3| crater_profile(noise::worley(uniforms.noise, &(point * self.crater_scale)).f1 / self.crater_radius);
+    let crater = crater_depth(&point).min(0.0);
+    let normal = bump::bump_normal(fragment, uniforms, self.bump_strength, 0.01 / self.crater_scale, |position| {
+        crater_depth(&noise::sphere_point(position))
+    });
+
+    let albedo = dust_albedo
+        .lerp(&self.frost_color, frost * 0.6)
+        .lerp(&ice_albedo, ice)
+        * (1.0 + crater * 0.4);
+    let roughness = self.dust_roughness + (self.ice_roughness - self.dust_roughness) * ice;
+    let point = SurfacePoint::dielectric(albedo, roughness);
+    lighting::shade(
+        uniforms.lighting,
+        &point,
+        &normal,
+        &lighting::view_dir(fragment, uniforms),
+        &lighting::light_dir(fragment, uniforms),
+    )
+  }
+}
 
-  let band_color = if band_noise > 0.75 {
-      band_color1
-  } else if band_noise > 0.5 {
-      band_color2
-  } else if band_noise > 0.25 {
-      band_color3
-  } else {
-      band_color4
-  };
 
-  let ring_opacity = ((2.5 - radius).clamp(0.0, 1.0) * 0.8).max(0.1);
+shader_params!(SurfaceShader {
+  ambient: f32 = 0.15,
+});
 
-  let final_color = if radius > 1.0 && radius < 2.5 {
-      ring_color * fragment.intensity * ring_opacity
-  } else {
-      band_color * fragment.intensity
-  };
+// Usa el material de superficie de la submalla (colores y texturas del archivo del modelo)
+impl FragmentShader for SurfaceShader {
+  fn name(&self) -> &'static str {
+    "surface"
+  }
 
-  final_color
+  fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let surface = uniforms.surface;
+    let uv = fragment.tex_coords;
+
+    let base_color = surface.diffuse_at(uv);
+    let (metallic, roughness) = surface.metallic_roughness_at(uv);
+    let normal = surface.normal_at(fragment);
+    let point = SurfacePoint {
+        albedo: base_color,
+        specular: surface.specular_at(uv),
+        metallic,
+        roughness,
+    };
+
+    // Luz ambiente uniforme: los metales la reflejan con su color base igual que los dieléctricos
+    let ambient = base_color * self.ambient;
+    let direct = lighting::shade(
+        uniforms.lighting,
+        &point,
+        &normal,
+        &lighting::view_dir(fragment, uniforms),
+        &lighting::light_dir(fragment, uniforms),
+    );
+
+    ambient + direct + surface.emissive_at(uv)
+  }
 }
-
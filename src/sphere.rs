@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::mesh::Mesh;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SphereKind {
+    Icosphere,
+    UvSphere,
+    Obj,
+}
+
+// Icosaedro subdividido: cada nivel divide cada triángulo en cuatro
+pub fn icosphere(subdivisions: u32, radius: f32) -> Mesh {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Los puntos medios se comparten entre triángulos vecinos
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(((positions[a as usize] + positions[b as usize]) * 0.5).normalize());
+                (positions.len() - 1) as u32
+            })
+        };
+
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut mesh = Mesh::new(
+        positions.into_iter().map(|position| sphere_vertex(position, radius)).collect(),
+        faces.into_iter().flatten().collect(),
+    );
+    mesh.compute_tangents();
+    mesh
+}
+
+// Esfera por meridianos y paralelos; la costura repite vértices para que las coordenadas de textura sean continuas
+pub fn uv_sphere(segments: u32, rings: u32, radius: f32) -> Mesh {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+
+    let mut vertices = Vec::with_capacity(((rings + 1) * (segments + 1)) as usize);
+    for ring in 0..=rings {
+        let theta = PI * ring as f32 / rings as f32;
+        for segment in 0..=segments {
+            let phi = 2.0 * PI * segment as f32 / segments as f32;
+            let direction = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            let mut vertex = sphere_vertex(direction, radius);
+            vertex.tex_coords = Vec2::new(segment as f32 / segments as f32, ring as f32 / rings as f32);
+            vertices.push(vertex);
+        }
+    }
+
+    // Los polos no generan triángulos degenerados
+    let index = |ring: u32, segment: u32| ring * (segments + 1) + segment;
+    let mut indices = Vec::new();
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let top_left = index(ring, segment);
+            let top_right = index(ring, segment + 1);
+            let bottom_left = index(ring + 1, segment);
+            let bottom_right = index(ring + 1, segment + 1);
+
+            if ring > 0 {
+                indices.extend([top_left, top_right, bottom_left]);
+            }
+            if ring + 1 < rings {
+                indices.extend([top_right, bottom_right, bottom_left]);
+            }
+        }
+    }
+    let mut mesh = Mesh::new(vertices, indices);
+    mesh.compute_tangents();
+    mesh
+}
+
+// Anillo plano en el plano xz, inclinado `tilt` radianes alrededor del eje x.
+// La inclinación queda en la malla para que la luz fija (+z) ilumine su cara superior
+pub fn ring(inner: f32, outer: f32, segments: u32, tilt: f32) -> Mesh {
+    let segments = segments.max(3);
+    let rotation = nalgebra_glm::rotation(tilt, &Vec3::x_axis());
+    let tilted = |point: Vec3| (rotation * point.push(0.0)).xyz();
+    let normal = tilted(Vec3::y());
+
+    let mut vertices = Vec::with_capacity(((segments + 1) * 2) as usize);
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let phi = 2.0 * PI * u;
+        let direction = Vec3::new(phi.cos(), 0.0, phi.sin());
+        vertices.push(Vertex::new(tilted(direction * inner), normal, Vec2::new(u, 0.0)));
+        vertices.push(Vertex::new(tilted(direction * outer), normal, Vec2::new(u, 1.0)));
+    }
+
+    let mut indices = Vec::with_capacity((segments * 6) as usize);
+    for segment in 0..segments {
+        let inner_start = segment * 2;
+        let (outer_start, inner_end, outer_end) = (inner_start + 1, inner_start + 2, inner_start + 3);
+        indices.extend([inner_start, inner_end, outer_start, outer_start, inner_end, outer_end]);
+    }
+    let mut mesh = Mesh::new(vertices, indices);
+    mesh.compute_tangents();
+    mesh
+}
+
+fn sphere_vertex(direction: Vec3, radius: f32) -> Vertex {
+    let tex_coords = Vec2::new(
+        0.5 + direction.z.atan2(direction.x) / (2.0 * PI),
+        direction.y.clamp(-1.0, 1.0).acos() / PI,
+    );
+    Vertex::new(direction * radius, direction, tex_coords)
+}
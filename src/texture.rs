@@ -0,0 +1,43 @@
+use std::path::Path;
+use nalgebra_glm::Vec2;
+use crate::color::Color;
+
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|err| format!("No se pudo cargar la textura {}: {}", path.display(), err))?
+            .to_rgb8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels = image.pixels().map(|pixel| Color::new(pixel[0], pixel[1], pixel[2])).collect();
+
+        Ok(Texture::from_pixels(width, height, pixels))
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "tamaño de textura inconsistente");
+        Texture { width, height, pixels }
+    }
+
+    // Muestreo bilineal con repetición en ambos ejes
+    pub fn sample(&self, uv: Vec2) -> Color {
+        let x = uv.x.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = uv.y.rem_euclid(1.0) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let texel = |x: f32, y: f32| {
+            let x = (x as i64).rem_euclid(self.width as i64) as usize;
+            let y = (y as i64).rem_euclid(self.height as i64) as usize;
+            self.pixels[y * self.width + x]
+        };
+        let top = texel(x0, y0).lerp(&texel(x0 + 1.0, y0), tx);
+        let bottom = texel(x0, y0 + 1.0).lerp(&texel(x0 + 1.0, y0 + 1.0), tx);
+        top.lerp(&bottom, ty)
+    }
+}
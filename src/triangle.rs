@@ -1,84 +1,299 @@
-use nalgebra_glm::{Vec3, dot};
+use nalgebra_glm::{Vec3, Vec4};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::color::Color;
 use rayon::prelude::*;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
+const SAMPLES_1X: [(f32, f32); 1] = [(0.5, 0.5)];
+
+// Patrón de rejilla rotada (RGSS) para 4x MSAA
+const SAMPLES_4X: [(f32, f32); 4] = [
+  (0.375, 0.125),
+  (0.875, 0.375),
+  (0.125, 0.625),
+  (0.625, 0.875),
+];
+
+pub fn sample_pattern(samples: usize) -> &'static [(f32, f32)] {
+  match samples {
+    4 => &SAMPLES_4X,
+    _ => &SAMPLES_1X,
+  }
+}
+
+// Precisión sub-píxel de las funciones de borde (punto fijo 24.8)
+const SUBPIXEL_BITS: i64 = 8;
+const FIXED_ONE: i64 = 1 << SUBPIXEL_BITS;
+const MAX_POINTS: usize = 5;
+// Banda de guarda en píxeles: fuera de ella un vértice se descarta en lugar de recortarse.
+// Con 2^20 px las funciones de borde en punto fijo caben holgadas en i64
+const GUARD_BAND: f32 = (1 << 20) as f32;
+
+type FixedPoint = (i64, i64);
+
+fn to_fixed(value: f32) -> i64 {
+  (value * FIXED_ONE as f32).round() as i64
+}
+
+struct Edge {
+  origin: FixedPoint,
+  step_x: i64,
+  step_y: i64,
+  bias: i64,
+}
+
+impl Edge {
+  fn new(from: FixedPoint, to: FixedPoint) -> Self {
+    // Regla top-left: los píxeles exactamente sobre un borde solo pertenecen al triángulo
+    // si el borde es superior (horizontal) o izquierdo
+    let is_top = from.1 == to.1 && to.0 > from.0;
+    let is_left = to.1 < from.1;
+
+    Edge {
+      origin: from,
+      step_x: from.1 - to.1,
+      step_y: to.0 - from.0,
+      bias: if is_top || is_left { 0 } else { -1 },
+    }
+  }
+
+  fn evaluate(&self, point: FixedPoint) -> i64 {
+    self.step_x * (point.0 - self.origin.0) + self.step_y * (point.1 - self.origin.1)
+  }
+
+  fn contains(&self, value: i64) -> bool {
+    value + self.bias >= 0
+  }
+}
+
+fn weights(values: &[i64; 3], area: i64) -> (f32, f32, f32) {
+  let area = area as f32;
+  (values[0] as f32 / area, values[1] as f32 / area, values[2] as f32 / area)
+}
+
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, viewport: (usize, usize), samples: usize) -> Vec<Fragment> {
   
   let mut fragments = Vec::new();
+
+  // También descarta los vértices detrás de la cámara, que transform_vertex deja en NaN
+  let inside_guard_band = |v: &Vertex| {
+    v.transformed_position.x.abs() <= GUARD_BAND && v.transformed_position.y.abs() <= GUARD_BAND
+  };
+  if ![v1, v2, v3].into_iter().all(inside_guard_band) {
+    return fragments;
+  }
+
+  let fixed = |v: &Vertex| (to_fixed(v.transformed_position.x), to_fixed(v.transformed_position.y));
+  let area = Edge::new(fixed(v1), fixed(v2)).evaluate(fixed(v3));
+  if area == 0 {
+    return fragments;
+  }
+
+  let (v1, v2, v3) = if area < 0 { (v1, v3, v2) } else { (v1, v2, v3) };
+  let area = area.abs();
+  let (p1, p2, p3) = (fixed(v1), fixed(v2), fixed(v3));
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
-  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  // El borde opuesto a cada vértice da su coordenada baricéntrica
+  let edges = [Edge::new(p2, p3), Edge::new(p3, p1), Edge::new(p1, p2)];
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+  let min_x = (p1.0.min(p2.0).min(p3.0) >> SUBPIXEL_BITS).max(0);
+  let min_y = (p1.1.min(p2.1).min(p3.1) >> SUBPIXEL_BITS).max(0);
+  let max_x = ((p1.0.max(p2.0).max(p3.0) + FIXED_ONE - 1) >> SUBPIXEL_BITS).min(viewport.0 as i64 - 1);
+  let max_y = ((p1.1.max(p2.1).max(p3.1) + FIXED_ONE - 1) >> SUBPIXEL_BITS).min(viewport.1 as i64 - 1);
+  if min_x > max_x || min_y > max_y {
+    return fragments;
+  }
+
+  // Puntos evaluados por píxel: las muestras del patrón y al final el centro del píxel
+  let pattern = sample_pattern(samples);
+  let center = pattern.len();
+  let mut offsets = [(FIXED_ONE / 2, FIXED_ONE / 2); MAX_POINTS];
+  for (offset, (offset_x, offset_y)) in offsets.iter_mut().zip(pattern) {
+    *offset = (to_fixed(*offset_x), to_fixed(*offset_y));
+  }
 
-  let triangle_area = edge_function(&a, &b, &c);
+  let mut row = [[0i64; 3]; MAX_POINTS];
+  for (values, offset) in row.iter_mut().zip(&offsets).take(center + 1) {
+    let point = (min_x * FIXED_ONE + offset.0, min_y * FIXED_ONE + offset.1);
+    *values = [edges[0].evaluate(point), edges[1].evaluate(point), edges[2].evaluate(point)];
+  }
+  let step_x = edges.each_ref().map(|edge| edge.step_x * FIXED_ONE);
+  let step_y = edges.each_ref().map(|edge| edge.step_y * FIXED_ONE);
 
   for y in min_y..=max_y {
+    let mut current = row;
+
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      let mut coverage = 0u8;
+      let mut sample_depths = [f32::INFINITY; 4];
+      let mut shading_point = None;
 
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+      for (sample, values) in current.iter().take(center).enumerate() {
+        if edges.iter().zip(values).all(|(edge, value)| edge.contains(*value)) {
+          let (w1, w2, w3) = weights(values, area);
+          coverage |= 1 << sample;
+          sample_depths[sample] = a.z * w1 + b.z * w2 + c.z * w3;
+          shading_point.get_or_insert((w1, w2, w3));
+        }
+      }
 
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+      if let Some(first_covered) = shading_point {
+        // Sombreado una vez por píxel: en el centro si está cubierto, si no en la primera muestra cubierta
+        let (w1, w2, w3) = if current[center].iter().all(|value| *value >= 0) {
+          weights(&current[center], area)
+        } else {
+          first_covered
+        };
 
         let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
         let normal = normal.normalize();
 
-        let intensity = dot(&normal, &light_dir).max(0.0);
-
-        let base_color = Color::new(100, 100, 100);
-        let lit_color = base_color * intensity;
-
         let depth = a.z * w1 + b.z * w2 + c.z * w3;
 
         let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+        let tex_coords = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
+        let tangent = (v1.transformed_tangent.xyz() * w1
+            + v2.transformed_tangent.xyz() * w2
+            + v3.transformed_tangent.xyz() * w3)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+        let tangent = Vec4::new(tangent.x, tangent.y, tangent.z, v1.transformed_tangent.w);
 
         fragments.push(
-            Fragment::new(
-                x as f32,
-                y as f32,
-                lit_color,
-                depth,
-                normal,
-                intensity,
-                vertex_position,
-            )
+            Fragment::new(x as f32, y as f32, depth, normal, vertex_position)
+            .with_surface(tex_coords, tangent)
+            .with_coverage(coverage, sample_depths)
         );
       }
+
+      for values in current.iter_mut().take(center + 1) {
+        for (value, step) in values.iter_mut().zip(&step_x) {
+          *value += step;
+        }
+      }
+    }
+
+    for values in row.iter_mut().take(center + 1) {
+      for (value, step) in values.iter_mut().zip(&step_y) {
+        *value += step;
+      }
     }
   }
 
   fragments
 }
-pub fn rasterize_parallel(triangles: &[[Vertex; 3]]) -> Vec<Fragment> {
+
+// Los triángulos referencian por índice a los vértices ya transformados
+pub fn rasterize_parallel(
+  vertices: &[Vertex],
+  triangles: &[[u32; 3]],
+  viewport: (usize, usize),
+  samples: usize,
+) -> Vec<Fragment> {
   triangles
       .par_iter()
-      .flat_map(|tri| triangle(&tri[0], &tri[1], &tri[2]))
+      .flat_map(|&[a, b, c]| {
+        triangle(&vertices[a as usize], &vertices[b as usize], &vertices[c as usize], viewport, samples)
+      })
       .collect() 
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
-    let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
-    let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
-    let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
-    let max_y = v1.y.max(v2.y).max(v3.y).ceil() as i32;
+#[cfg(test)]
+mod tests {
+  use super::*;
 
-    (min_x, min_y, max_x, max_y)
-}
+  const VIEWPORT: (usize, usize) = (24, 24);
+  const CELLS: usize = 8;
+  const CELL_SIZE: f32 = 3.25;
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
+  fn vertex(x: f32, y: f32) -> Vertex {
+    Vertex::new(Vec3::new(x, y, 0.5), Vec3::new(0.0, 0.0, 1.0), nalgebra_glm::Vec2::zeros())
+  }
 
-    (w1, w2, w3)
-}
+  // Rejilla de quads con bordes compartidos que cubre todo el viewport. Los vértices se
+  // desplazan en múltiplos de 1/16 de píxel para caer a menudo justo sobre las muestras
+  fn jittered_grid() -> Vec<Vertex> {
+    let mut state = 0x2545_f491_u32;
+    let mut jitter = || {
+      state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+      ((state >> 16) % 25) as f32 / 16.0 - 0.75
+    };
 
-fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
-    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
-}
+    let mut vertices = Vec::new();
+    for row in 0..=CELLS {
+      for column in 0..=CELLS {
+        let (jitter_x, jitter_y) = (jitter(), jitter());
+        vertices.push(vertex(column as f32 * CELL_SIZE - 1.0 + jitter_x, row as f32 * CELL_SIZE - 1.0 + jitter_y));
+      }
+    }
+    vertices
+  }
+
+  // Dos triángulos por quad; la diagonal y el sentido de giro alternan
+  fn grid_triangles() -> Vec<[u32; 3]> {
+    let index = |row: usize, column: usize| (row * (CELLS + 1) + column) as u32;
+    let mut triangles = Vec::new();
+    for row in 0..CELLS {
+      for column in 0..CELLS {
+        let (a, b, c, d) = (index(row, column), index(row, column + 1), index(row + 1, column + 1), index(row + 1, column));
+        if (row + column) % 2 == 0 {
+          triangles.push([a, b, c]);
+          triangles.push([a, d, c]);
+        } else {
+          triangles.push([b, c, d]);
+          triangles.push([d, a, b]);
+        }
+      }
+    }
+    triangles
+  }
 
+  fn sample_counts(samples: usize) -> Vec<[u32; 4]> {
+    let fragments = rasterize_parallel(&jittered_grid(), &grid_triangles(), VIEWPORT, samples);
+    let mut counts = vec![[0u32; 4]; VIEWPORT.0 * VIEWPORT.1];
+    for fragment in &fragments {
+      let pixel = &mut counts[fragment.position.y as usize * VIEWPORT.0 + fragment.position.x as usize];
+      for (sample, count) in pixel.iter_mut().enumerate().take(samples) {
+        if fragment.coverage & (1 << sample) != 0 {
+          *count += 1;
+        }
+      }
+    }
+    counts
+  }
+
+  #[test]
+  fn shared_edges_cover_each_pixel_once() {
+    for (pixel, counts) in sample_counts(1).iter().enumerate() {
+      assert_eq!(counts[0], 1, "píxel ({}, {})", pixel % VIEWPORT.0, pixel / VIEWPORT.0);
+    }
+  }
+
+  #[test]
+  fn shared_edges_cover_each_sample_once() {
+    for (pixel, counts) in sample_counts(4).iter().enumerate() {
+      assert_eq!(counts, &[1; 4], "píxel ({}, {})", pixel % VIEWPORT.0, pixel / VIEWPORT.0);
+    }
+  }
+
+  #[test]
+  fn vertices_outside_the_guard_band_are_rejected() {
+    let (a, b) = (vertex(1.0, 1.0), vertex(20.0, 2.0));
+    for far in [vertex(1e12, 5.0), vertex(3.0, -1e12), vertex(f32::INFINITY, 5.0), vertex(5.0, f32::NEG_INFINITY), vertex(f32::NAN, 5.0)] {
+      for samples in [1, 4] {
+        assert!(triangle(&a, &b, &far, VIEWPORT, samples).is_empty());
+        assert!(triangle(&far, &a, &b, VIEWPORT, samples).is_empty());
+      }
+    }
+  }
+
+  #[test]
+  fn degenerate_triangles_produce_no_fragments() {
+    let (a, b, c) = (vertex(1.0, 1.0), vertex(5.5, 3.25), vertex(10.0, 5.5));
+    for samples in [1, 4] {
+      assert!(triangle(&a, &b, &c, VIEWPORT, samples).is_empty());
+      assert!(triangle(&a, &a, &c, VIEWPORT, samples).is_empty());
+      assert!(triangle(&b, &b, &b, VIEWPORT, samples).is_empty());
+    }
+  }
+}
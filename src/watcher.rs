@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// Revisa periódicamente la fecha de modificación de un archivo
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    interval: Duration,
+}
+
+impl FileWatcher {
+    pub fn new(path: &str) -> Self {
+        let path = PathBuf::from(path);
+        FileWatcher {
+            modified: modified_time(&path),
+            path,
+            last_check: Instant::now(),
+            interval: Duration::from_millis(500),
+        }
+    }
+
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < self.interval {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified_time(&self.path);
+        if modified.is_some() && modified != self.modified {
+            self.modified = modified;
+            return true;
+        }
+        false
+    }
+
+    pub fn mark_current(&mut self) {
+        self.modified = modified_time(&self.path);
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}